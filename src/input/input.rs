@@ -1,11 +1,92 @@
-use clap::Parser;
+use crate::proximity_graph::proximity_graph::ProximityGraph;
+use crate::tour_generation::brightness::BrightnessCost;
+use crate::tour_generation::metric::Metric;
+use crate::tour_generation::multi_tour::ClusterBy;
+use crate::tour_generation::optimizer::TourOptimizer;
+use crate::tour_generation::path::PathEnds;
+use crate::tour_generation::registry::StrategyRegistry;
+use crate::tour_generation::strokes::JumpThreshold;
+use crate::tour_generation::tour_strategy::TourConfig;
+use clap::builder::PossibleValuesParser;
+use clap::{Parser, ValueEnum};
+use image::GrayImage;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum MetricArg {
+    Euclidean,
+    Squared,
+    Manhattan,
+    Plotter,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClusterByArg {
+    Position,
+    Color,
+    /// Position and color
+    Both,
+}
+
+impl From<ClusterByArg> for ClusterBy {
+    fn from(arg: ClusterByArg) -> Self {
+        match arg {
+            ClusterByArg::Position => ClusterBy::Position,
+            ClusterByArg::Color => ClusterBy::Color,
+            ClusterByArg::Both => ClusterBy::PositionAndColor,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ProximityGraphArg {
+    /// Euclidean minimum spanning tree
+    Mst,
+    /// Relative neighborhood graph
+    Rng,
+    Gabriel,
+    Delaunay,
+}
+
+impl From<ProximityGraphArg> for ProximityGraph {
+    fn from(arg: ProximityGraphArg) -> Self {
+        match arg {
+            ProximityGraphArg::Mst => ProximityGraph::MinimumSpanningTree,
+            ProximityGraphArg::Rng => ProximityGraph::RelativeNeighborhood,
+            ProximityGraphArg::Gabriel => ProximityGraph::Gabriel,
+            ProximityGraphArg::Delaunay => ProximityGraph::Delaunay,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum TourOptimizerArg {
+    #[value(name = "2opt")]
+    TwoOpt,
+    #[value(name = "oropt")]
+    OrOpt,
+    /// Lin-Kernighan style variable-depth search
+    #[value(name = "lk", alias = "or3opt")]
+    LinKernighan,
+}
+
+impl From<TourOptimizerArg> for TourOptimizer {
+    fn from(arg: TourOptimizerArg) -> Self {
+        match arg {
+            TourOptimizerArg::TwoOpt => TourOptimizer::TwoOpt,
+            TourOptimizerArg::OrOpt => TourOptimizer::OrOpt,
+            TourOptimizerArg::LinKernighan => TourOptimizer::LinKernighan,
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     /// Path to input image
-    #[clap(short, long)]
-    pub image: String,
+    #[clap(short, long, required_unless_present = "frames")]
+    pub image: Option<String>,
 
     /// Path to output file
     #[clap(short, long, default_value = "output.svg")]
@@ -46,4 +127,241 @@ pub struct Args {
     /// Draw fourier epicycles [default: false]
     #[clap(short, long, default_value_t = false)]
     pub fourier_epicycles: bool,
+
+    /// Directory of video frames to draw one after the other, each tour warm-started
+    /// from the tour of the previous frame so the line does not flicker [default: none]
+    #[clap(
+        long,
+        requires = "tour",
+        conflicts_with_all = [
+            "image",
+            "tours",
+            "fourier_epicycles",
+            "export_tsp",
+            "export_tour",
+            "import_tour",
+            "lower_bound",
+            "tour_stats",
+        ]
+    )]
+    pub frames: Option<String>,
+
+    /// Directory the SVG of every frame is written to [default: frames_out]
+    #[clap(long, default_value = "frames_out", requires = "frames")]
+    pub frames_out: String,
+
+    /// Draw the edges of a proximity graph over the points instead of a tour [default: none]
+    #[clap(
+        long,
+        value_enum,
+        conflicts_with_all = [
+            "tour",
+            "fourier_epicycles",
+            "export_tour",
+            "import_tour",
+            "lower_bound",
+            "tour_stats",
+        ]
+    )]
+    pub graph: Option<ProximityGraphArg>,
+
+    /// Tour construction strategy, "auto" picks cheapest insertion up to 2048 points
+    /// and greedy above [default: auto]
+    #[clap(long, default_value = "auto", value_parser = tour_strategy_parser())]
+    pub tour_strategy: String,
+
+    /// Strategy used for every cell by the partition strategy [default: cheapest-insertion]
+    #[clap(long, default_value = "cheapest-insertion", value_parser = tour_strategy_parser())]
+    pub partition_cell_strategy: String,
+
+    /// Maximum number of points per cell of the partition strategy [default: 5000]
+    #[clap(long, default_value_t = 5000)]
+    pub partition_cell_size: usize,
+
+    /// Number of moves the annealing strategy cools over, which makes its tour depend
+    /// only on the seed. The time budget still stops it early [default: none]
+    #[clap(long)]
+    pub annealing_moves: Option<usize>,
+
+    /// Number of separate closed tours, e.g. one per pen [default: 1]
+    #[clap(
+        long,
+        default_value_t = 1,
+        requires = "tour",
+        conflicts_with_all = [
+            "fourier_epicycles",
+            "open_path",
+            "path_start",
+            "path_end",
+            "max_jump",
+            "max_jump_median",
+            "lower_bound",
+            "tour_stats",
+        ]
+    )]
+    pub tours: usize,
+
+    /// What the points are grouped by when drawing several tours [default: position]
+    #[clap(long, value_enum, default_value_t = ClusterByArg::Position)]
+    pub tour_clustering: ClusterByArg,
+
+    /// Write the stipple points to this TSPLIB .tsp file [default: none]
+    #[clap(long)]
+    pub export_tsp: Option<String>,
+
+    /// Factor the coordinates are multiplied by in the .tsp file [default: 100.0]
+    #[clap(long, default_value_t = 100.0)]
+    pub tsp_scale: f32,
+
+    /// Write the tour to this TSPLIB .tour file [default: none]
+    #[clap(long, conflicts_with = "tours")]
+    pub export_tour: Option<String>,
+
+    /// Draw this TSPLIB .tour file, e.g. from an external solver, instead of
+    /// building a tour [default: none]
+    #[clap(long, conflicts_with = "tours")]
+    pub import_tour: Option<String>,
+
+    /// Time budget in seconds for improving the tour [default: none]
+    #[clap(long)]
+    pub tour_time_budget: Option<f32>,
+
+    /// Comma separated improvement passes run after construction, e.g. 2opt,oropt
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub tour_optimizer: Vec<TourOptimizerArg>,
+
+    /// Print the Held–Karp lower bound and the optimality gap of the tour. Above 500 points
+    /// the bound is computed over a sparse nearest-neighbor graph and may slightly exceed
+    /// the optimal length [default: false]
+    #[clap(long, default_value_t = false)]
+    pub lower_bound: bool,
+
+    /// Print edge length statistics and the self-intersection count of the tour [default: false]
+    #[clap(long, default_value_t = false)]
+    pub tour_stats: bool,
+
+    /// Draw an open path instead of a closed tour [default: false]
+    #[clap(long, default_value_t = false)]
+    pub open_path: bool,
+
+    /// Position "x,y" the open path starts near, implies --open-path
+    #[clap(long, value_parser = parse_position)]
+    pub path_start: Option<(f32, f32)>,
+
+    /// Position "x,y" the open path ends near, implies --open-path
+    #[clap(long, value_parser = parse_position)]
+    pub path_end: Option<(f32, f32)>,
+
+    /// Split the tour into separate strokes at edges longer than this length [default: none]
+    #[clap(long, conflicts_with = "max_jump_median")]
+    pub max_jump: Option<f32>,
+
+    /// Split the tour into separate strokes at edges longer than this multiple of the
+    /// median edge length [default: none]
+    #[clap(long)]
+    pub max_jump_median: Option<f32>,
+
+    /// Draw the tour as one smooth curve through the points [default: false]
+    #[clap(
+        long,
+        default_value_t = false,
+        requires = "tour",
+        conflicts_with_all = ["tours", "max_jump", "max_jump_median"]
+    )]
+    pub smooth: bool,
+
+    /// Tension of the smooth curve, from 0 for round curves to 1 for straight lines
+    /// [default: 0.0]
+    #[clap(long, default_value_t = 0.0, requires = "smooth")]
+    pub smooth_tension: f32,
+
+    /// Penalty per pixel of edge length on white, which keeps the tour out of bright
+    /// regions [default: none]
+    #[clap(long)]
+    pub brightness_weight: Option<f32>,
+
+    /// Penalty per radian of turning at each point, which smooths the tour [default: 0.0]
+    #[clap(long, default_value_t = 0.0)]
+    pub turn_penalty: f32,
+
+    /// Distance metric minimized by the tour [default: euclidean]
+    #[clap(long, value_enum, default_value_t = MetricArg::Euclidean)]
+    pub metric: MetricArg,
+
+    /// Plotter x axis speed, used by the plotter metric [default: 1.0]
+    #[clap(long, default_value_t = 1.0)]
+    pub plotter_x_speed: f32,
+
+    /// Plotter y axis speed, used by the plotter metric [default: 1.0]
+    #[clap(long, default_value_t = 1.0)]
+    pub plotter_y_speed: f32,
+}
+
+fn parse_position(value: &str) -> Result<(f32, f32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected x,y but got '{}'", value))?;
+    let parse = |coordinate: &str| {
+        coordinate
+            .trim()
+            .parse::<f32>()
+            .map_err(|err| format!("invalid coordinate '{}': {}", coordinate, err))
+    };
+    Ok((parse(x)?, parse(y)?))
+}
+
+fn tour_strategy_parser() -> PossibleValuesParser {
+    let registry = StrategyRegistry::with_defaults();
+    PossibleValuesParser::new(registry.names().into_iter().map(String::from))
+}
+
+impl Args {
+    pub fn tour_optimizers(&self) -> Vec<TourOptimizer> {
+        self.tour_optimizer.iter().map(|&arg| arg.into()).collect()
+    }
+
+    pub fn metric(&self) -> Metric {
+        match self.metric {
+            MetricArg::Euclidean => Metric::Euclidean,
+            MetricArg::Squared => Metric::SquaredEuclidean,
+            MetricArg::Manhattan => Metric::Manhattan,
+            MetricArg::Plotter => Metric::Plotter {
+                x_speed: self.plotter_x_speed,
+                y_speed: self.plotter_y_speed,
+            },
+        }
+    }
+
+    pub fn path(&self) -> Option<PathEnds> {
+        if self.open_path || self.path_start.is_some() || self.path_end.is_some() {
+            Some(PathEnds {
+                start: self.path_start,
+                end: self.path_end,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn jump_threshold(&self) -> Option<JumpThreshold> {
+        match (self.max_jump, self.max_jump_median) {
+            (Some(length), _) => Some(JumpThreshold::Absolute(length)),
+            (None, Some(factor)) => Some(JumpThreshold::MedianMultiple(factor)),
+            (None, None) => None,
+        }
+    }
+
+    /// Tour settings, with the brightness penalty sampled from `image`.
+    pub fn tour_config(&self, seed: u64, image: &GrayImage) -> TourConfig {
+        TourConfig {
+            seed,
+            time_budget: self.tour_time_budget.map(Duration::from_secs_f32),
+            metric: self.metric(),
+            path: self.path(),
+            brightness: self
+                .brightness_weight
+                .map(|weight| Arc::new(BrightnessCost::new(image, weight))),
+            turn_penalty: self.turn_penalty,
+        }
+    }
 }
@@ -1,7 +1,9 @@
 pub mod fourier_epicycle;
 pub mod image;
 pub mod input;
+pub mod proximity_graph;
 pub mod save_svg;
 pub mod stippling;
 pub mod svg_generator;
 pub mod tour_generation;
+pub mod tsplib;
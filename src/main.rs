@@ -1,88 +1,237 @@
-mod fourier_epicycle;
-mod image;
-mod input;
-mod save_svg;
-mod stippling;
-mod svg_generator;
-mod tour_generation;
-
-use crate::fourier_epicycle::fourier::{compute_fourier_series, compute_position};
-use crate::image::image_processing::load_and_grayscale;
-use crate::input::input::Args;
-use crate::save_svg::save::save_batch;
-use crate::stippling::stippling::generate_stippling;
-use crate::svg_generator::svg_generator::{
-    add_voronoi_cells, generate_fourier_svg, generate_svg_stippling, generate_tsp_svg,
-};
-use crate::tour_generation::tour_strategy::{CheapestInsertionStrategy, GreedyStrategy, Tour};
 use clap::Parser;
-use geo::{ConvexHull, MultiPoint, Point};
+use std::fs;
+use std::path::{Path, PathBuf};
 use svg::Document;
+use tsp_art::fourier_epicycle::fourier::{compute_fourier_series, compute_position};
+use tsp_art::image::image_processing::load_and_grayscale;
+use tsp_art::input::input::Args;
+use tsp_art::proximity_graph::proximity_graph::ProximityGraph;
+use tsp_art::save_svg::save::save_batch;
+use tsp_art::stippling::point::PointColor;
+use tsp_art::stippling::stippling::generate_stippling;
+use tsp_art::svg_generator::smooth::{catmull_rom_beziers, straighten_crossings};
+use tsp_art::svg_generator::svg_generator::{
+    add_voronoi_cells, generate_fourier_svg, generate_graph_svg, generate_multi_tour_svg,
+    generate_smooth_tsp_svg, generate_strokes_svg, generate_svg_stippling, generate_tsp_svg,
+};
+use tsp_art::tour_generation::lower_bound::HeldKarpBound;
+use tsp_art::tour_generation::metric::Metric;
+use tsp_art::tour_generation::multi_tour::{ClusterBy, Clustering, build_multi_tour};
+use tsp_art::tour_generation::optimizer::optimize_chain;
+use tsp_art::tour_generation::path::EdgeCost;
+use tsp_art::tour_generation::registry::StrategyRegistry;
+use tsp_art::tour_generation::stats::TourStats;
+use tsp_art::tour_generation::strokes::{order_strokes, pen_up_travel, split_strokes};
+use tsp_art::tour_generation::tour_strategy::{PartitionStrategy, SimulatedAnnealingStrategy};
+use tsp_art::tour_generation::uncross::uncross;
+use tsp_art::tour_generation::warm_start::warm_start_tour;
+use tsp_art::tsplib::tsplib::{read_tour, write_tour, write_tsp};
 
 fn main() {
     let args = Args::parse();
+    let seed = 42;
 
-    let image_path = args.image;
-    let output_path = args.output;
+    if let Some(frames_dir) = &args.frames {
+        draw_frames(&args, frames_dir, seed);
+        return;
+    }
+
+    let image_path: &str = args.image.as_deref().expect("Missing input image");
+    let output_path = &args.output;
     let num_points: usize = args.points;
     let iterations: usize = args.iterations;
-    let seed = 42;
+    let tour_optimizers = args.tour_optimizers();
 
-    let (image, grayscale_image) = load_and_grayscale(&image_path);
+    let (image, grayscale_image) = load_and_grayscale(image_path);
+    let tour_config = args.tour_config(seed, &grayscale_image);
     let (width, height) = grayscale_image.dimensions();
 
     let (points, darkness_values, colors) =
         generate_stippling(&grayscale_image, &image, num_points, seed, iterations);
 
-    let mut svg = Document::new()
-        .set("viewBox", (0, 0, width, height))
-        .set("width", width)
-        .set("height", height)
-        .set("style", "background-color: white");
-
-    svg = generate_svg_stippling(
-        svg,
-        &points,
-        &darkness_values,
-        &colors,
-        args.min_radius,
-        args.max_radius,
-    );
-
-    if args.voronoid_cells {
-        svg = add_voronoi_cells(svg, &points, width, height);
+    // Solvers are run on the .tsp file separately. Stippling is seeded, so the same
+    // image and options reproduce the points to draw their tour on.
+    let name: String = Path::new(image_path)
+        .file_stem()
+        .map_or("tsp-art".to_string(), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+    if let Some(tsp_path) = &args.export_tsp {
+        write_tsp(Path::new(tsp_path), &name, &points, args.tsp_scale)
+            .expect("Failed to write TSPLIB problem");
     }
 
-    if args.tour || args.fourier_epicycles {
-        let geo_points: Vec<Point<f32>> = points.iter().map(|&(x, y)| Point::new(x, y)).collect();
-        let multi_point = MultiPoint::from(geo_points);
-        let hull = multi_point.convex_hull();
-        let hull_points: Vec<Point<f32>> = hull.exterior().points().collect();
+    let mut svg = stippling_document(&args, &points, &darkness_values, &colors, width, height);
 
-        let tour = if args.points > 2048 {
-            let tsp = Tour::new(GreedyStrategy);
-            tsp.tour(&points, &hull_points)
+    if let Some(graph) = args.graph {
+        let graph: ProximityGraph = graph.into();
+        let edges: Vec<(usize, usize)> = graph.edges(&points);
+        println!("Graph edges: {}", edges.len());
+        svg = generate_graph_svg(
+            svg,
+            &points,
+            &edges,
+            &darkness_values,
+            &colors,
+            args.min_stroke_width,
+            args.max_stroke_width,
+            None,
+        );
+    } else if args.tours > 1 {
+        let cluster_by: ClusterBy = args.tour_clustering.into();
+        let clusters: Vec<Vec<usize>> = Clustering {
+            k: args.tours,
+            by: cluster_by,
+            ..Clustering::default()
+        }
+        .clusters(&points, &colors, seed);
+        let registry = strategy_registry(&args);
+        let strategy = registry
+            .get(&args.tour_strategy)
+            .expect("Failed to find tour strategy");
+        let mut multi_tour = build_multi_tour(strategy, &points, &clusters, &tour_config)
+            .expect("Failed to build tours");
+        let cost = EdgeCost::from_config(&points, &tour_config);
+        for (i, tour) in multi_tour.tours_mut().iter_mut().enumerate() {
+            optimize_chain(&points, tour.order_mut(), &tour_config, &tour_optimizers);
+            if !uncross(&cost, tour.order_mut()) {
+                println!("Warning: tour {} still touches itself", i);
+            }
+            println!(
+                "Tour {}: {} points, length {:.2}",
+                i,
+                tour.len(),
+                tour.length(&points, tour_config.metric)
+            );
+        }
+        println!(
+            "Total length: {:.2}",
+            multi_tour.length(&points, tour_config.metric)
+        );
+
+        // Tours grouped by color are drawn in the mean color of their points.
+        let tours: Vec<Vec<usize>> = multi_tour
+            .into_tours()
+            .into_iter()
+            .map(|tour| tour.into_order())
+            .collect();
+        let tour_colors: Option<Vec<(u8, u8, u8)>> = (cluster_by != ClusterBy::Position)
+            .then(|| tours.iter().map(|tour| mean_color(&colors, tour)).collect());
+        svg = generate_multi_tour_svg(
+            svg,
+            &points,
+            &tours,
+            &darkness_values,
+            &colors,
+            args.min_stroke_width,
+            args.max_stroke_width,
+            tour_colors.as_deref(),
+        );
+    } else if args.tour
+        || args.fourier_epicycles
+        || args.export_tour.is_some()
+        || args.lower_bound
+        || args.tour_stats
+    {
+        let mut tour = match &args.import_tour {
+            Some(tour_path) => {
+                let mut tour =
+                    read_tour(Path::new(tour_path), points.len()).expect("Failed to read tour");
+                EdgeCost::from_config(&points, &tour_config).cut(tour.order_mut());
+                tour
+            }
+            None => strategy_registry(&args)
+                .build_tour(&args.tour_strategy, &points, &tour_config)
+                .expect("Failed to build tour"),
+        };
+        optimize_chain(&points, tour.order_mut(), &tour_config, &tour_optimizers);
+        let closed: bool = tour_config.path.is_none();
+        // Imported tours are kept as they are, apart from the optimizers asked for.
+        let cost = EdgeCost::from_config(&points, &tour_config);
+        if args.import_tour.is_none() && !uncross(&cost, tour.order_mut()) {
+            println!("Warning: the tour still touches itself");
+        }
+        let tour_length: f32 = if closed {
+            tour.length(&points, tour_config.metric)
         } else {
-            let tsp = Tour::new(CheapestInsertionStrategy);
-            tsp.tour(&points, &hull_points)
+            tour_config.metric.path_length(&points, tour.order())
         };
-
+        if args.lower_bound && !closed {
+            println!(
+                "Path length: {:.2}, the lower bound is only computed for closed tours",
+                tour_length
+            );
+        } else if args.lower_bound {
+            let lower_bound: f32 = HeldKarpBound {
+                metric: tour_config.metric,
+                ..HeldKarpBound::default()
+            }
+            .lower_bound(&points, tour_length);
+            if lower_bound > 0.0 {
+                println!(
+                    "Tour length: {:.2}, lower bound: {:.2}, gap: {:.2}%",
+                    tour_length,
+                    lower_bound,
+                    100.0 * (tour_length - lower_bound) / lower_bound
+                );
+            } else {
+                println!(
+                    "Tour length: {:.2}, lower bound: {:.2}",
+                    tour_length, lower_bound
+                );
+            }
+        } else if closed {
+            println!("Tour length: {:.2}", tour_length);
+        } else {
+            println!("Path length: {:.2}", tour_length);
+        }
+        if args.tour_stats {
+            let stats = TourStats::new(&points, tour.order(), tour_config.metric, closed)
+                .expect("Invalid tour");
+            println!(
+                "Edges: mean {:.2}, max {:.2}, self-intersections {}",
+                stats.mean_edge, stats.max_edge, stats.self_intersections
+            );
+            println!(
+                "Turns: mean {:.1}°, max {:.1}°",
+                stats.mean_turn_angle.to_degrees(),
+                stats.max_turn_angle.to_degrees()
+            );
+            for (bin, count) in stats.histogram.iter().enumerate() {
+                println!(
+                    "  {:>8.2} - {:>8.2}: {}",
+                    bin as f32 * stats.histogram_bin_width,
+                    (bin + 1) as f32 * stats.histogram_bin_width,
+                    count
+                );
+            }
+        }
+        if let Some(tour_path) = &args.export_tour {
+            write_tour(Path::new(tour_path), &name, tour.order()).expect("Failed to write tour");
+        }
+        let tour = tour.into_order();
         if args.tour {
-            svg = generate_tsp_svg(
+            svg = draw_tour(
                 svg,
+                &args,
                 &points,
                 &tour,
                 &darkness_values,
                 &colors,
-                args.min_stroke_width,
-                args.max_stroke_width,
-                None,
+                tour_config.metric,
+                closed,
             );
         }
 
         if args.fourier_epicycles {
             let output_dir = "frames_out";
-            let tour_points: Vec<(f32, f32)> = tour.iter().map(|&i| points[i]).collect();
+            let mut tour_points: Vec<(f32, f32)> = tour.iter().map(|&i| points[i]).collect();
+            // The Fourier series is periodic, so an open path is traced there and back.
+            if !closed {
+                let inner = 1..tour_points.len().saturating_sub(1);
+                let back: Vec<(f32, f32)> = tour_points[inner].iter().rev().copied().collect();
+                tour_points.extend(back);
+            }
 
             let (c_0, epicycles) = compute_fourier_series(&tour_points, num_points);
 
@@ -117,5 +266,219 @@ fn main() {
         }
     }
 
-    svg::save(&output_path, &svg).expect("Failed to save SVG");
+    svg::save(output_path, &svg).expect("Failed to save SVG");
+}
+
+/// Draws the tour of every image in `frames_dir`, in file name order. Every tour after
+/// the first starts from the previous one, so consecutive frames keep the same line.
+fn draw_frames(args: &Args, frames_dir: &str, seed: u64) {
+    let mut frame_paths: Vec<PathBuf> = fs::read_dir(frames_dir)
+        .expect("Failed to read frames directory")
+        .map(|entry| entry.expect("Failed to read frames directory").path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    ["png", "jpg", "jpeg"].contains(&extension.to_lowercase().as_str())
+                })
+        })
+        .collect();
+    frame_paths.sort();
+    fs::create_dir_all(&args.frames_out).expect("Failed to create output directory");
+
+    let registry = strategy_registry(args);
+    let tour_optimizers = args.tour_optimizers();
+    // Points and tour of the previous frame, empty for the first one.
+    let mut previous_points: Vec<(f32, f32)> = Vec::new();
+    let mut previous_tour: Vec<usize> = Vec::new();
+    for frame_path in &frame_paths {
+        let (image, grayscale_image) =
+            load_and_grayscale(frame_path.to_str().expect("Invalid frame path"));
+        let tour_config = args.tour_config(seed, &grayscale_image);
+        let (width, height) = grayscale_image.dimensions();
+        let (points, darkness_values, colors) =
+            generate_stippling(&grayscale_image, &image, args.points, seed, args.iterations);
+
+        let mut tour = if previous_tour.is_empty() {
+            registry.build_tour(&args.tour_strategy, &points, &tour_config)
+        } else {
+            warm_start_tour(&previous_points, &previous_tour, &points, &tour_config)
+        }
+        .expect("Failed to build tour");
+        optimize_chain(&points, tour.order_mut(), &tour_config, &tour_optimizers);
+        let closed: bool = tour_config.path.is_none();
+        let cost = EdgeCost::from_config(&points, &tour_config);
+        let simple: bool = uncross(&cost, tour.order_mut());
+
+        let name = frame_path.file_stem().expect("Invalid frame path");
+        if !simple {
+            println!(
+                "Warning: the tour of {} still touches itself",
+                name.to_string_lossy()
+            );
+        }
+        let tour_length: f32 = if closed {
+            tour.length(&points, tour_config.metric)
+        } else {
+            tour_config.metric.path_length(&points, tour.order())
+        };
+        println!("{}: length {:.2}", name.to_string_lossy(), tour_length);
+
+        let svg = stippling_document(args, &points, &darkness_values, &colors, width, height);
+        let svg = draw_tour(
+            svg,
+            args,
+            &points,
+            tour.order(),
+            &darkness_values,
+            &colors,
+            tour_config.metric,
+            closed,
+        );
+        let output_path = Path::new(&args.frames_out).join(name).with_extension("svg");
+        svg::save(output_path, &svg).expect("Failed to save SVG frame");
+        previous_points = points;
+        previous_tour = tour.into_order();
+    }
+}
+
+/// Default strategies, with annealing progress reports and the annealing and partition
+/// strategies set up from the command line.
+fn strategy_registry(args: &Args) -> StrategyRegistry {
+    let mut registry = StrategyRegistry::with_defaults();
+    registry.register(
+        "annealing",
+        Box::new(SimulatedAnnealingStrategy {
+            progress: Some(Box::new(|progress| {
+                println!(
+                    "Annealing {:.1}s: length {:.2} (best {:.2})",
+                    progress.elapsed.as_secs_f32(),
+                    progress.tour_length,
+                    progress.best_length
+                )
+            })),
+            moves: args.annealing_moves,
+            ..SimulatedAnnealingStrategy::default()
+        }),
+    );
+    let cell_strategy = StrategyRegistry::with_defaults()
+        .remove(&args.partition_cell_strategy)
+        .expect("Failed to find partition cell strategy");
+    registry.register(
+        "partition",
+        Box::new(PartitionStrategy {
+            cell_strategy,
+            cell_size: args.partition_cell_size,
+        }),
+    );
+    registry
+}
+
+/// Document of the image size with the stipple points, and their Voronoi cells when
+/// asked for.
+fn stippling_document(
+    args: &Args,
+    points: &[(f32, f32)],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    width: u32,
+    height: u32,
+) -> Document {
+    let mut svg = Document::new()
+        .set("viewBox", (0, 0, width, height))
+        .set("width", width)
+        .set("height", height)
+        .set("style", "background-color: white");
+
+    svg = generate_svg_stippling(
+        svg,
+        points,
+        darkness_values,
+        colors,
+        args.min_radius,
+        args.max_radius,
+    );
+
+    if args.voronoid_cells {
+        svg = add_voronoi_cells(svg, points, width, height);
+    }
+    svg
+}
+
+/// Draws the tour as straight lines, as strokes split at long jumps or as a smooth
+/// curve, depending on the command line.
+#[allow(clippy::too_many_arguments)]
+fn draw_tour(
+    svg: Document,
+    args: &Args,
+    points: &[(f32, f32)],
+    tour: &[usize],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    metric: Metric,
+    closed: bool,
+) -> Document {
+    if let Some(threshold) = args.jump_threshold() {
+        let mut strokes = split_strokes(points, tour, metric, threshold, closed);
+        order_strokes(points, &mut strokes, metric);
+        println!(
+            "Strokes: {}, pen-up travel: {:.2}",
+            strokes.len(),
+            pen_up_travel(points, &strokes, metric)
+        );
+        generate_strokes_svg(
+            svg,
+            points,
+            &strokes,
+            darkness_values,
+            colors,
+            args.min_stroke_width,
+            args.max_stroke_width,
+            None,
+        )
+    } else if args.smooth {
+        let mut curves = catmull_rom_beziers(points, tour, args.smooth_tension, closed);
+        let straightened: usize = straighten_crossings(&mut curves, closed);
+        if straightened > 0 {
+            println!(
+                "Smoothing: {} curves kept straight to avoid crossings",
+                straightened
+            );
+        }
+        generate_smooth_tsp_svg(
+            svg,
+            tour,
+            &curves,
+            darkness_values,
+            colors,
+            args.min_stroke_width,
+            args.max_stroke_width,
+            None,
+            closed,
+        )
+    } else {
+        generate_tsp_svg(
+            svg,
+            points,
+            tour,
+            darkness_values,
+            colors,
+            args.min_stroke_width,
+            args.max_stroke_width,
+            None,
+            closed,
+        )
+    }
+}
+
+fn mean_color(colors: &[PointColor], tour: &[usize]) -> (u8, u8, u8) {
+    let len = tour.len().max(1) as u32;
+    let (r, g, b) = tour.iter().fold((0u32, 0u32, 0u32), |sum, &idx| {
+        (
+            sum.0 + colors[idx].r as u32,
+            sum.1 + colors[idx].g as u32,
+            sum.2 + colors[idx].b as u32,
+        )
+    });
+    ((r / len) as u8, (g / len) as u8, (b / len) as u8)
 }
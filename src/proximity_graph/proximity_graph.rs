@@ -0,0 +1,263 @@
+use crate::tour_generation::hull::compare;
+use crate::tour_generation::utils::{find, group_by_position};
+use kiddo::{KdTree, SquaredEuclidean};
+use voronator::delaunator::{INVALID_INDEX, Point, next_halfedge, prev_halfedge, triangulate};
+
+/// Line art made of the edges of a graph over the stipple points, from the sparsest to
+/// the densest. Every graph contains the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProximityGraph {
+    /// Euclidean minimum spanning tree.
+    MinimumSpanningTree,
+    /// Edges whose lune, the intersection of the two circles around either end through
+    /// the other one, contains no other point.
+    RelativeNeighborhood,
+    /// Edges whose diametral circle contains no other point.
+    Gabriel,
+    /// Delaunay triangulation.
+    Delaunay,
+}
+
+/// Delaunay edge with the third corners of the one or two triangles next to it.
+struct DelaunayEdge {
+    a: usize,
+    b: usize,
+    opposite: [Option<usize>; 2],
+}
+
+impl ProximityGraph {
+    /// Edges of the graph as pairs of point indices, all derived from the Delaunay
+    /// triangulation in O(n log n). Points at the same position are joined by
+    /// zero-length edges, so that the spanning tree still spans every point.
+    pub fn edges(self, points: &[(f32, f32)]) -> Vec<(usize, usize)> {
+        let (unique, mut edges) = deduplicate(points);
+        let delaunay: Vec<DelaunayEdge> = delaunay_edges(points, &unique);
+        let distance = |a: usize, b: usize| squared_distance(points[a], points[b]);
+
+        match self {
+            ProximityGraph::Delaunay => {
+                edges.extend(delaunay.iter().map(|edge| (edge.a, edge.b)));
+            }
+            ProximityGraph::Gabriel => {
+                edges.extend(gabriel_edges(points, &delaunay));
+            }
+            ProximityGraph::RelativeNeighborhood => {
+                let mut kdtree = KdTree::<f32, 2>::with_capacity(unique.len());
+                for &idx in &unique {
+                    kdtree.add(&[points[idx].0, points[idx].1], idx as u64);
+                }
+                // The lune lies inside the diametral circle, so only Gabriel edges
+                // can qualify.
+                edges.extend(gabriel_edges(points, &delaunay).filter(|&(a, b)| {
+                    let length: f32 = distance(a, b);
+                    kdtree
+                        .within_unsorted::<SquaredEuclidean>(&[points[a].0, points[a].1], length)
+                        .iter()
+                        .all(|neighbor| {
+                            let c: usize = neighbor.item as usize;
+                            c == b || neighbor.distance >= length || distance(b, c) >= length
+                        })
+                }));
+            }
+            ProximityGraph::MinimumSpanningTree => {
+                // Kruskal's algorithm, the spanning tree is a subgraph of the
+                // triangulation.
+                let mut sorted: Vec<(usize, usize)> =
+                    delaunay.iter().map(|edge| (edge.a, edge.b)).collect();
+                sorted.sort_by(|&(a, b), &(c, d)| distance(a, b).total_cmp(&distance(c, d)));
+                let mut parent: Vec<usize> = (0..points.len()).collect();
+                edges.extend(sorted.into_iter().filter(|&(a, b)| {
+                    let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+                    parent[root_a] = root_b;
+                    root_a != root_b
+                }));
+            }
+        }
+        edges
+    }
+}
+
+/// Delaunay edges that are also Gabriel edges: the angles opposite of them are at most
+/// 90°, so the circle with the edge as diameter is empty.
+fn gabriel_edges<'a>(
+    points: &'a [(f32, f32)],
+    delaunay: &'a [DelaunayEdge],
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    delaunay
+        .iter()
+        .filter(|edge| {
+            let (a, b) = (points[edge.a], points[edge.b]);
+            edge.opposite.iter().flatten().all(|&c| {
+                let c: (f32, f32) = points[c];
+                (a.0 - c.0) * (b.0 - c.0) + (a.1 - c.1) * (b.1 - c.1) >= 0.0
+            })
+        })
+        .map(|edge| (edge.a, edge.b))
+}
+
+/// Edges of the Delaunay triangulation of the points `unique`, which are sorted by
+/// position. Fewer than three or collinear points have no triangulation and are
+/// chained in sorted order instead.
+fn delaunay_edges(points: &[(f32, f32)], unique: &[usize]) -> Vec<DelaunayEdge> {
+    let coords: Vec<Point> = unique
+        .iter()
+        .map(|&idx| Point {
+            x: points[idx].0 as f64,
+            y: points[idx].1 as f64,
+        })
+        .collect();
+    let Some(triangulation) = triangulate(&coords) else {
+        return unique
+            .windows(2)
+            .map(|pair| DelaunayEdge {
+                a: pair[0],
+                b: pair[1],
+                opposite: [None, None],
+            })
+            .collect();
+    };
+
+    let corner = |halfedge: usize| unique[triangulation.triangles[halfedge]];
+    (0..triangulation.triangles.len())
+        .filter_map(|e| {
+            // Every inner edge has two halfedges, keep the one with the lower index.
+            let twin: usize = triangulation.halfedges[e];
+            if twin != INVALID_INDEX && twin < e {
+                return None;
+            }
+            Some(DelaunayEdge {
+                a: corner(e),
+                b: corner(next_halfedge(e)),
+                opposite: [
+                    Some(corner(prev_halfedge(e))),
+                    (twin != INVALID_INDEX).then(|| corner(prev_halfedge(twin))),
+                ],
+            })
+        })
+        .collect()
+}
+
+/// Indices of the distinct positions, sorted by position, and zero-length edges from
+/// every other point to the point at the same position.
+fn deduplicate(points: &[(f32, f32)]) -> (Vec<usize>, Vec<(usize, usize)>) {
+    let indices: Vec<usize> = (0..points.len()).collect();
+    let groups: Vec<Vec<usize>> = group_by_position(points, &indices);
+    let mut unique: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+    unique.sort_by(|&a, &b| compare(points[a], points[b]));
+    let duplicates: Vec<(usize, usize)> = groups
+        .iter()
+        .flat_map(|group| group[1..].iter().map(|&idx| (group[0], idx)))
+        .collect();
+    (unique, duplicates)
+}
+
+fn squared_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    const GRAPHS: [ProximityGraph; 4] = [
+        ProximityGraph::MinimumSpanningTree,
+        ProximityGraph::RelativeNeighborhood,
+        ProximityGraph::Gabriel,
+        ProximityGraph::Delaunay,
+    ];
+
+    fn edge_set(graph: ProximityGraph, points: &[(f32, f32)]) -> BTreeSet<(usize, usize)> {
+        let edges: Vec<(usize, usize)> = graph.edges(points);
+        let set: BTreeSet<(usize, usize)> =
+            edges.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        assert_eq!(set.len(), edges.len(), "{:?} lists an edge twice", graph);
+        set
+    }
+
+    /// Checks that the MST spans the points and every graph contains the previous one.
+    fn assert_nested(points: &[(f32, f32)]) {
+        let graphs: Vec<BTreeSet<(usize, usize)>> = GRAPHS
+            .iter()
+            .map(|&graph| edge_set(graph, points))
+            .collect();
+        let mst: &BTreeSet<(usize, usize)> = &graphs[0];
+        assert_eq!(mst.len(), points.len() - 1);
+        let mut parent: Vec<usize> = (0..points.len()).collect();
+        for &(a, b) in mst {
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            assert_ne!(root_a, root_b, "the MST has a cycle");
+            parent[root_a] = root_b;
+        }
+        for pair in graphs.windows(2) {
+            assert!(pair[0].is_subset(&pair[1]));
+        }
+    }
+
+    #[test]
+    fn graphs_of_random_points_are_nested() {
+        for seed in 0..5 {
+            let mut rng: StdRng = StdRng::seed_from_u64(seed);
+            let points: Vec<(f32, f32)> = (0..300)
+                .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+                .collect();
+            assert_nested(&points);
+        }
+    }
+
+    #[test]
+    fn graphs_of_coincident_points_are_nested() {
+        // Stipple points are rounded to pixels, so many share a position.
+        let mut rng: StdRng = StdRng::seed_from_u64(9);
+        let points: Vec<(f32, f32)> = (0..400)
+            .map(|_| {
+                (
+                    rng.random_range(0..15) as f32,
+                    rng.random_range(0..15) as f32,
+                )
+            })
+            .collect();
+        assert_nested(&points);
+        // Points at one position form a star around the first of them.
+        let indices: Vec<usize> = (0..points.len()).collect();
+        let groups: Vec<Vec<usize>> = group_by_position(&points, &indices);
+        assert!(groups.len() < points.len());
+        for graph in GRAPHS {
+            let edges: BTreeSet<(usize, usize)> = edge_set(graph, &points);
+            for group in &groups {
+                for &idx in &group[1..] {
+                    assert!(edges.contains(&(group[0], idx)), "{:?}", graph);
+                }
+            }
+        }
+
+        let same: Vec<(f32, f32)> = vec![(1.0, 2.0); 40];
+        for graph in GRAPHS {
+            assert_eq!(edge_set(graph, &same).len(), 39);
+        }
+    }
+
+    #[test]
+    fn gabriel_and_relative_neighborhood_match_their_definitions() {
+        let mut rng: StdRng = StdRng::seed_from_u64(4);
+        let points: Vec<(f32, f32)> = (0..60)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect();
+        let d = |a: usize, b: usize| squared_distance(points[a], points[b]);
+        let n: usize = points.len();
+        let pairs = || (0..n).flat_map(move |a| (a + 1..n).map(move |b| (a, b)));
+        let gabriel: BTreeSet<(usize, usize)> = pairs()
+            .filter(|&(a, b)| (0..n).all(|c| c == a || c == b || d(a, c) + d(b, c) >= d(a, b)))
+            .collect();
+        let relative: BTreeSet<(usize, usize)> = pairs()
+            .filter(|&(a, b)| (0..n).all(|c| c == a || c == b || d(a, c).max(d(b, c)) >= d(a, b)))
+            .collect();
+        assert_eq!(edge_set(ProximityGraph::Gabriel, &points), gabriel);
+        assert_eq!(
+            edge_set(ProximityGraph::RelativeNeighborhood, &points),
+            relative
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod point;
+#[allow(clippy::module_inception)]
 pub mod stippling;
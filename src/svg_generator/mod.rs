@@ -1 +1,3 @@
+pub mod smooth;
+#[allow(clippy::module_inception)]
 pub mod svg_generator;
@@ -0,0 +1,206 @@
+use crate::tour_generation::intersections::intersecting_edges;
+
+/// Straight pieces every curve is approximated by when looking for intersections.
+const FLATTEN_STEPS: usize = 16;
+
+/// Start point, two control points and end point of a cubic Bézier curve.
+pub type CubicBezier = [(f32, f32); 4];
+
+/// One cubic Bézier curve per tour edge, together a centripetal Catmull-Rom spline
+/// through the tour points. Centripetal parametrization never overshoots into loops or
+/// cusps within a curve, even where points are unevenly spaced.
+///
+/// `tension` goes from 0 for the full spline to 1 for straight lines. The ends of an
+/// open path leave towards their only neighbor.
+pub fn catmull_rom_beziers(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    tension: f32,
+    closed: bool,
+) -> Vec<CubicBezier> {
+    let n: usize = tour.len();
+    let num_curves: usize = if closed { n } else { n.saturating_sub(1) };
+    if n < 2 {
+        return Vec::new();
+    }
+    let scale: f32 = 1.0 - tension.clamp(0.0, 1.0);
+    let point = |i: usize| points[tour[i % n]];
+
+    (0..num_curves)
+        .map(|i| {
+            let (p1, p2) = (point(i), point(i + 1));
+            // Missing neighbors of the ends of an open path are mirrored.
+            let p0: (f32, f32) = if closed || i > 0 {
+                point(i + n - 1)
+            } else {
+                reflect(p2, p1)
+            };
+            let p3: (f32, f32) = if closed || i + 2 < n {
+                point(i + 2)
+            } else {
+                reflect(p1, p2)
+            };
+            let c1: (f32, f32) = control_point(p0, p1, p2);
+            let c2: (f32, f32) = control_point(p3, p2, p1);
+            [p1, lerp(p1, c1, scale), lerp(p2, c2, scale), p2]
+        })
+        .collect()
+}
+
+/// Replaces curves that intersect other curves by the straight tour edge, until the
+/// curves intersect no more than the straight edges do. Returns how many curves were
+/// straightened.
+pub fn straighten_crossings(curves: &mut [CubicBezier], closed: bool) -> usize {
+    let mut straight: Vec<bool> = curves.iter().map(is_straight).collect();
+    let mut straightened: usize = 0;
+    loop {
+        let samples: Vec<(f32, f32)> = flatten(curves, closed);
+        let order: Vec<usize> = (0..samples.len()).collect();
+        let mut changed: bool = false;
+        for (i, j) in intersecting_edges(&samples, &order, closed) {
+            for curve in [i / FLATTEN_STEPS, j / FLATTEN_STEPS] {
+                if !straight[curve] {
+                    let [start, _, _, end] = curves[curve];
+                    curves[curve] = [start, start, end, end];
+                    straight[curve] = true;
+                    straightened += 1;
+                    changed = true;
+                }
+            }
+        }
+        // Remaining intersections are between straight edges, e.g. at coinciding
+        // points, which smoothing cannot fix.
+        if !changed {
+            return straightened;
+        }
+    }
+}
+
+/// SVG path data with one `C` command per curve, closed with `Z` when `closed`.
+pub fn bezier_path_data(curves: &[CubicBezier], closed: bool) -> String {
+    let Some(first) = curves.first() else {
+        return String::new();
+    };
+    let mut path_data: String = format!("M {} {}", first[0].0, first[0].1);
+    for [_, c1, c2, end] in curves {
+        path_data.push_str(&format!(
+            " C {} {} {} {} {} {}",
+            c1.0, c1.1, c2.0, c2.1, end.0, end.1
+        ));
+    }
+    if closed {
+        path_data.push_str(" Z");
+    }
+    path_data
+}
+
+/// Point on `curve` at parameter `t` in 0..=1.
+pub fn evaluate(curve: &CubicBezier, t: f32) -> (f32, f32) {
+    let u: f32 = 1.0 - t;
+    let weights: [f32; 4] = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    curve
+        .iter()
+        .zip(weights)
+        .fold((0.0, 0.0), |sum, (p, w)| (sum.0 + w * p.0, sum.1 + w * p.1))
+}
+
+/// Polyline through `FLATTEN_STEPS` points per curve, so that piece `i` belongs to
+/// curve `i / FLATTEN_STEPS`.
+fn flatten(curves: &[CubicBezier], closed: bool) -> Vec<(f32, f32)> {
+    let mut samples: Vec<(f32, f32)> = curves
+        .iter()
+        .flat_map(|curve| {
+            (0..FLATTEN_STEPS).map(|k| evaluate(curve, k as f32 / FLATTEN_STEPS as f32))
+        })
+        .collect();
+    if !closed && let Some(last) = curves.last() {
+        samples.push(last[3]);
+    }
+    samples
+}
+
+/// First control point of the centripetal Catmull-Rom curve from `p1` to `p2`, where
+/// `p0` is the point before `p1`.
+fn control_point(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32)) -> (f32, f32) {
+    // With alpha = 1/2 the knot intervals are the square roots of the distances, so
+    // their squares are the distances themselves.
+    let d2_sq: f32 = distance(p1, p2);
+    if d2_sq == 0.0 {
+        return p1;
+    }
+    let p0: (f32, f32) = if p0 == p1 { reflect(p2, p1) } else { p0 };
+    let d1_sq: f32 = distance(p0, p1);
+    let (d1, d2) = (d1_sq.sqrt(), d2_sq.sqrt());
+    let a: f32 = 2.0 * d1_sq + 3.0 * d1 * d2 + d2_sq;
+    let denominator: f32 = 3.0 * d1 * (d1 + d2);
+    (
+        (d1_sq * p2.0 - d2_sq * p0.0 + a * p1.0) / denominator,
+        (d1_sq * p2.1 - d2_sq * p0.1 + a * p1.1) / denominator,
+    )
+}
+
+fn is_straight(curve: &CubicBezier) -> bool {
+    curve[1] == curve[0] && curve[2] == curve[3]
+}
+
+/// `p` mirrored at `center`.
+fn reflect(p: (f32, f32), center: (f32, f32)) -> (f32, f32) {
+    (2.0 * center.0 - p.0, 2.0 * center.1 - p.1)
+}
+
+fn lerp(a: (f32, f32), b: (f32, f32), t: f32) -> (f32, f32) {
+    (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour_generation::metric::Metric;
+    use crate::tour_generation::path::{EdgeCost, PathEnds};
+    use crate::tour_generation::uncross::uncross;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn straightened_curves_do_not_cross() {
+        let mut straightened: usize = 0;
+        for seed in 0..3 {
+            let mut rng: StdRng = StdRng::seed_from_u64(seed);
+            let points: Vec<(f32, f32)> = (0..200)
+                .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+                .collect();
+            for closed in [true, false] {
+                let mut tour: Vec<usize> = (0..points.len()).collect();
+                tour.shuffle(&mut rng);
+                let path: Option<PathEnds> = (!closed).then(PathEnds::default);
+                let cost = EdgeCost::new(&points, Metric::Euclidean, path, None, 0.0);
+                assert!(uncross(&cost, &mut tour));
+
+                let mut curves: Vec<CubicBezier> = catmull_rom_beziers(&points, &tour, 0.0, closed);
+                straightened += straighten_crossings(&mut curves, closed);
+                let samples: Vec<(f32, f32)> = flatten(&curves, closed);
+                let order: Vec<usize> = (0..samples.len()).collect();
+                assert_eq!(intersecting_edges(&samples, &order, closed), Vec::new());
+                for (curve, &idx) in curves.iter().zip(&tour) {
+                    assert_eq!(curve[0], points[idx]);
+                }
+            }
+        }
+        // The spline through a tour this jagged crosses itself somewhere.
+        assert!(straightened > 0);
+    }
+
+    #[test]
+    fn full_tension_gives_straight_curves() {
+        let points = [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0)];
+        let mut curves: Vec<CubicBezier> = catmull_rom_beziers(&points, &[0, 1, 2], 1.0, true);
+        assert!(curves.iter().all(is_straight));
+        assert_eq!(straighten_crossings(&mut curves, true), 0);
+        assert_eq!(evaluate(&curves[0], 0.5), (2.0, 0.0));
+    }
+}
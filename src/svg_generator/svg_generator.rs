@@ -1,3 +1,4 @@
+use super::smooth::{CubicBezier, bezier_path_data};
 use crate::fourier_epicycle::fourier::Epicycle;
 use crate::stippling::point::PointColor;
 use rustfft::num_complex::Complex;
@@ -74,10 +75,12 @@ pub fn add_voronoi_cells(
     document
 }
 
-pub fn generate_tsp_svg(
+/// Draws every stroke as one `<path>`, with the mean darkness and color of its points.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_strokes_svg(
     mut document: Document,
     points: &[(f32, f32)],
-    tour: &[usize],
+    strokes: &[Vec<usize>],
     darkness_values: &[f32],
     colors: &[PointColor],
     min_stroke_width: f32,
@@ -86,56 +89,285 @@ pub fn generate_tsp_svg(
 ) -> Document {
     let max_darkness = darkness_values.iter().cloned().fold(0.0, f32::max);
 
-    let mut path_segments = Vec::new();
-    let mut stroke_widths = Vec::new();
-    let mut segment_colors = Vec::new();
+    for stroke in strokes.iter().filter(|stroke| stroke.len() >= 2) {
+        let (stroke_width, stroke_color) = stroke_style(
+            stroke,
+            darkness_values,
+            max_darkness,
+            colors,
+            min_stroke_width,
+            max_stroke_width,
+            line_color,
+        );
 
-    for i in 0..tour.len() {
-        let current_idx = tour[i];
-        let next_idx = tour[(i + 1) % tour.len()];
-        let (x1, y1) = points[current_idx];
-        let (x2, y2) = points[next_idx];
+        let (x, y) = points[stroke[0]];
+        let mut path_data = format!("M {} {}", x, y);
+        for &idx in &stroke[1..] {
+            let (x, y) = points[idx];
+            path_data.push_str(&format!(" L {} {}", x, y));
+        }
+        let path = Path::new()
+            .set("d", path_data)
+            .set("fill", "none")
+            .set("stroke", stroke_color)
+            .set("stroke-width", stroke_width)
+            .set("stroke-opacity", 0.8)
+            .set("stroke-linecap", "round")
+            .set("stroke-linejoin", "round");
+        document = document.add(path);
+    }
 
-        let avg_darkness = (darkness_values[current_idx] + darkness_values[next_idx]) / 2.0;
-        let normalized_avg_darkness = if max_darkness > 0.0 {
-            avg_darkness / max_darkness
-        } else {
-            0.0
-        };
-        let stroke_width =
-            min_stroke_width + normalized_avg_darkness * (max_stroke_width - min_stroke_width);
-        stroke_widths.push(stroke_width);
-
-        let segment_color = match line_color {
-            Some(color) => format!("rgb({},{},{})", color.0, color.1, color.2),
-            None => {
-                let avg_r = (colors[current_idx].r as u16 + colors[next_idx].r as u16) / 2;
-                let avg_g = (colors[current_idx].g as u16 + colors[next_idx].g as u16) / 2;
-                let avg_b = (colors[current_idx].b as u16 + colors[next_idx].b as u16) / 2;
-                format!("rgb({},{},{})", avg_r, avg_g, avg_b)
-            }
-        };
-        segment_colors.push(segment_color);
+    document
+}
 
-        path_segments.push((x1, y1, x2, y2));
+/// Draws the tour as one smooth `<path>` made of the Bézier `curves` along its edges,
+/// with the mean darkness and color of its points.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_smooth_tsp_svg(
+    document: Document,
+    tour: &[usize],
+    curves: &[CubicBezier],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+    closed: bool,
+) -> Document {
+    if curves.is_empty() {
+        return document;
     }
+    let max_darkness = darkness_values.iter().cloned().fold(0.0, f32::max);
+    let (stroke_width, stroke_color) = stroke_style(
+        tour,
+        darkness_values,
+        max_darkness,
+        colors,
+        min_stroke_width,
+        max_stroke_width,
+        line_color,
+    );
+    let path = Path::new()
+        .set("d", bezier_path_data(curves, closed))
+        .set("fill", "none")
+        .set("stroke", stroke_color)
+        .set("stroke-width", stroke_width)
+        .set("stroke-opacity", 0.8)
+        .set("stroke-linecap", "round")
+        .set("stroke-linejoin", "round");
+    document.add(path)
+}
 
-    for (i, (x1, y1, x2, y2)) in path_segments.iter().enumerate() {
-        let line = Line::new()
-            .set("x1", *x1)
-            .set("y1", *y1)
-            .set("x2", *x2)
-            .set("y2", *y2)
-            .set("stroke", &*segment_colors[i])
-            .set("stroke-width", stroke_widths[i])
-            .set("stroke-opacity", 0.8)
-            .set("stroke-linecap", "round");
+#[allow(clippy::too_many_arguments)]
+pub fn generate_tsp_svg(
+    mut document: Document,
+    points: &[(f32, f32)],
+    tour: &[usize],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+    closed: bool,
+) -> Document {
+    for line in tour_lines(
+        points,
+        tour,
+        darkness_values,
+        colors,
+        min_stroke_width,
+        max_stroke_width,
+        line_color,
+        closed,
+    ) {
         document = document.add(line);
     }
+    document
+}
 
+/// Draws every closed tour in its own `<g>`, so that each can be plotted with a
+/// different pen. A tour is drawn in its color from `tour_colors` when given.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_multi_tour_svg(
+    mut document: Document,
+    points: &[(f32, f32)],
+    tours: &[Vec<usize>],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    tour_colors: Option<&[(u8, u8, u8)]>,
+) -> Document {
+    for (i, tour) in tours.iter().enumerate() {
+        let mut group = Group::new().set("id", format!("tour-{}", i));
+        for line in tour_lines(
+            points,
+            tour,
+            darkness_values,
+            colors,
+            min_stroke_width,
+            max_stroke_width,
+            tour_colors.map(|tour_colors| tour_colors[i]),
+            true,
+        ) {
+            group = group.add(line);
+        }
+        document = document.add(group);
+    }
     document
 }
 
+/// Draws every edge of a graph over the points, e.g. a proximity graph, like the
+/// edges of a tour.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_graph_svg(
+    mut document: Document,
+    points: &[(f32, f32)],
+    edges: &[(usize, usize)],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+) -> Document {
+    let max_darkness = darkness_values.iter().cloned().fold(0.0, f32::max);
+    for &(a, b) in edges {
+        document = document.add(edge_line(
+            points,
+            a,
+            b,
+            darkness_values,
+            max_darkness,
+            colors,
+            min_stroke_width,
+            max_stroke_width,
+            line_color,
+        ));
+    }
+    document
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tour_lines(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    darkness_values: &[f32],
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+    closed: bool,
+) -> Vec<Line> {
+    let max_darkness = darkness_values.iter().cloned().fold(0.0, f32::max);
+
+    // An open path has no edge back to its first point.
+    let num_segments = if closed {
+        tour.len()
+    } else {
+        tour.len().saturating_sub(1)
+    };
+    (0..num_segments)
+        .map(|i| {
+            edge_line(
+                points,
+                tour[i],
+                tour[(i + 1) % tour.len()],
+                darkness_values,
+                max_darkness,
+                colors,
+                min_stroke_width,
+                max_stroke_width,
+                line_color,
+            )
+        })
+        .collect()
+}
+
+/// Width from the mean darkness of the points of a stroke, and their mean color unless
+/// `line_color` is given.
+fn stroke_style(
+    stroke: &[usize],
+    darkness_values: &[f32],
+    max_darkness: f32,
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+) -> (f32, String) {
+    let count = stroke.len() as f32;
+    let avg_darkness = stroke.iter().map(|&idx| darkness_values[idx]).sum::<f32>() / count;
+    let normalized_avg_darkness = if max_darkness > 0.0 {
+        avg_darkness / max_darkness
+    } else {
+        0.0
+    };
+    let stroke_width =
+        min_stroke_width + normalized_avg_darkness * (max_stroke_width - min_stroke_width);
+
+    let stroke_color = match line_color {
+        Some(color) => format!("rgb({},{},{})", color.0, color.1, color.2),
+        None => {
+            let (r, g, b) = stroke.iter().fold((0u32, 0u32, 0u32), |sum, &idx| {
+                (
+                    sum.0 + colors[idx].r as u32,
+                    sum.1 + colors[idx].g as u32,
+                    sum.2 + colors[idx].b as u32,
+                )
+            });
+            let len = stroke.len() as u32;
+            format!("rgb({},{},{})", r / len, g / len, b / len)
+        }
+    };
+    (stroke_width, stroke_color)
+}
+
+/// Line between two points, as wide as their mean darkness and in their mean color
+/// unless `line_color` is given.
+#[allow(clippy::too_many_arguments)]
+fn edge_line(
+    points: &[(f32, f32)],
+    current_idx: usize,
+    next_idx: usize,
+    darkness_values: &[f32],
+    max_darkness: f32,
+    colors: &[PointColor],
+    min_stroke_width: f32,
+    max_stroke_width: f32,
+    line_color: Option<(u8, u8, u8)>,
+) -> Line {
+    let (x1, y1) = points[current_idx];
+    let (x2, y2) = points[next_idx];
+
+    let avg_darkness = (darkness_values[current_idx] + darkness_values[next_idx]) / 2.0;
+    let normalized_avg_darkness = if max_darkness > 0.0 {
+        avg_darkness / max_darkness
+    } else {
+        0.0
+    };
+    let stroke_width =
+        min_stroke_width + normalized_avg_darkness * (max_stroke_width - min_stroke_width);
+
+    let segment_color = match line_color {
+        Some(color) => format!("rgb({},{},{})", color.0, color.1, color.2),
+        None => {
+            let avg_r = (colors[current_idx].r as u16 + colors[next_idx].r as u16) / 2;
+            let avg_g = (colors[current_idx].g as u16 + colors[next_idx].g as u16) / 2;
+            let avg_b = (colors[current_idx].b as u16 + colors[next_idx].b as u16) / 2;
+            format!("rgb({},{},{})", avg_r, avg_g, avg_b)
+        }
+    };
+
+    Line::new()
+        .set("x1", x1)
+        .set("y1", y1)
+        .set("x2", x2)
+        .set("y2", y2)
+        .set("stroke", segment_color)
+        .set("stroke-width", stroke_width)
+        .set("stroke-opacity", 0.8)
+        .set("stroke-linecap", "round")
+}
+
 pub fn generate_fourier_svg(
     mut document: Document,
     c_0: Complex<f32>,
@@ -151,12 +383,8 @@ pub fn generate_fourier_svg(
     let max_radius = epicycles.iter().map(|e| e.radius).fold(0.0, f32::max);
 
     let mut epicycles_group = Group::new();
-    let mut count_epicycles = 0;
 
-    for epicycle in epicycles {
-        if count_epicycles > 64 {
-            break;
-        }
+    for epicycle in epicycles.iter().take(65) {
         let angle = 2.0 * std::f32::consts::PI * epicycle.freq as f32 * time + epicycle.phase;
         let dx = epicycle.radius * angle.cos();
         let dy = epicycle.radius * angle.sin();
@@ -187,13 +415,12 @@ pub fn generate_fourier_svg(
             .set("stroke-width", 3.0 * opacity)
             .set("stroke-dasharray", "7,5");
         epicycles_group = epicycles_group.add(line);
-        count_epicycles += 1;
     }
 
     let mut path_data = match previous_path_data {
         Some(data) => data.to_string(),
         None => {
-            if trace_points.len() >= 1 {
+            if !trace_points.is_empty() {
                 format!("M {} {}", trace_points[0].0, trace_points[0].1)
             } else {
                 String::new()
@@ -0,0 +1,14 @@
+use super::tour_strategy::{
+    AutoStrategy, CheapestInsertionStrategy, GreedyStrategy, Tour, TourConfig, TourError,
+    TourStrategy,
+};
+
+impl TourStrategy for AutoStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        if points.len() > self.threshold {
+            GreedyStrategy.build_tour(points, config)
+        } else {
+            CheapestInsertionStrategy.build_tour(points, config)
+        }
+    }
+}
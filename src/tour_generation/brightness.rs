@@ -0,0 +1,60 @@
+use image::GrayImage;
+
+/// Distance between two brightness samples along an edge, in pixels.
+const SAMPLE_SPACING: f32 = 1.0;
+
+/// Upper bound on the samples per edge, which keeps long edges cheap to evaluate.
+const MAX_SAMPLES: usize = 16;
+
+/// Extra edge cost for crossing bright parts of the image.
+///
+/// The penalty of an edge is `weight` times the brightness integrated along it, with
+/// brightness going from 0 for black to 1 for white pixels. A tour that minimizes it
+/// stays in dark regions instead of streaking across highlights.
+#[derive(Clone, Debug)]
+pub struct BrightnessCost {
+    width: usize,
+    height: usize,
+    brightness: Vec<f32>,
+    pub weight: f32,
+}
+
+impl BrightnessCost {
+    pub fn new(image: &GrayImage, weight: f32) -> Self {
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            brightness: image
+                .pixels()
+                .map(|pixel| pixel.0[0] as f32 / 255.0)
+                .collect(),
+            weight,
+        }
+    }
+
+    /// Brightness of the pixel under `p`, clamped to the image.
+    #[inline]
+    pub fn at(&self, p: (f32, f32)) -> f32 {
+        if self.brightness.is_empty() {
+            return 0.0;
+        }
+        let x: usize = (p.0.max(0.0) as usize).min(self.width - 1);
+        let y: usize = (p.1.max(0.0) as usize).min(self.height - 1);
+        self.brightness[y * self.width + x]
+    }
+
+    /// Penalty of the edge from `p1` to `p2`, sampled at the midpoints of equal
+    /// sub-segments.
+    pub fn penalty(&self, p1: (f32, f32), p2: (f32, f32)) -> f32 {
+        let (dx, dy) = (p2.0 - p1.0, p2.1 - p1.1);
+        let length: f32 = (dx * dx + dy * dy).sqrt();
+        let samples: usize = ((length / SAMPLE_SPACING).ceil() as usize).clamp(1, MAX_SAMPLES);
+        let sum: f32 = (0..samples)
+            .map(|i| {
+                let t: f32 = (i as f32 + 0.5) / samples as f32;
+                self.at((p1.0 + t * dx, p1.1 + t * dy))
+            })
+            .sum();
+        self.weight * length * sum / samples as f32
+    }
+}
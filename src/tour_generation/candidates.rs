@@ -0,0 +1,107 @@
+use super::metric::Metric;
+use super::path::EdgeCost;
+use super::utils::group_by_position;
+use kiddo::{KdTree, SquaredEuclidean};
+use rayon::prelude::*;
+
+/// Nearest-neighbor candidate lists used to restrict local search moves.
+/// Lists are indexed by point index and sorted from nearest to farthest under the
+/// metric they were built for. Neighbors are gathered with a Euclidean kd-tree query,
+/// which is a close enough approximation for the other metrics.
+pub struct CandidateLists {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl CandidateLists {
+    /// Builds the `k` nearest candidates of every point in `points`.
+    pub fn new(points: &[(f32, f32)], k: usize, metric: Metric) -> Self {
+        let cities: Vec<usize> = (0..points.len()).collect();
+        Self::from_subset(points, &cities, k, metric)
+    }
+
+    /// Builds the `k` nearest candidates of every point in `cities`, considering only
+    /// neighbors that are themselves in `cities`. Other points get an empty list.
+    pub fn from_subset(points: &[(f32, f32)], cities: &[usize], k: usize, metric: Metric) -> Self {
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+        let k: usize = k.min(cities.len().saturating_sub(1));
+        if k == 0 {
+            return Self { neighbors };
+        }
+
+        // Points at the same position share one kd-tree entry, which is expanded into
+        // all of them. The k + 1 nearest positions always hold k other points.
+        let groups: Vec<Vec<usize>> = group_by_position(points, cities);
+        let mut kdtree = KdTree::<f32, 2>::with_capacity(groups.len());
+        for (group, members) in groups.iter().enumerate() {
+            let (x, y) = points[members[0]];
+            kdtree.add(&[x, y], group as u64);
+        }
+
+        let lists: Vec<(usize, Vec<usize>)> = groups
+            .par_iter()
+            .flat_map_iter(|members| {
+                let (x, y) = points[members[0]];
+                let nearest: Vec<usize> = kdtree
+                    .nearest_n::<SquaredEuclidean>(&[x, y], k + 1)
+                    .into_iter()
+                    .flat_map(|neighbor| groups[neighbor.item as usize].iter().copied())
+                    .collect();
+                members.iter().map(move |&idx| {
+                    let mut list: Vec<usize> = nearest
+                        .iter()
+                        .copied()
+                        .filter(|&neighbor| neighbor != idx)
+                        .take(k)
+                        .collect();
+                    if metric != Metric::Euclidean && metric != Metric::SquaredEuclidean {
+                        list.sort_by(|&a, &b| {
+                            metric
+                                .distance(points[idx], points[a])
+                                .total_cmp(&metric.distance(points[idx], points[b]))
+                        });
+                    }
+                    (idx, list)
+                })
+            })
+            .collect();
+
+        for (idx, list) in lists {
+            neighbors[idx] = list;
+        }
+        Self { neighbors }
+    }
+
+    /// Builds the `k` nearest candidates of every node of `nodes` under `cost`. The
+    /// virtual end of an open path gets the points nearest to its pinned position, or
+    /// no candidates when that end is free.
+    pub fn from_nodes(cost: &EdgeCost, nodes: &[usize], k: usize) -> Self {
+        let points: &[(f32, f32)] = cost.points();
+        let cities: Vec<usize> = nodes
+            .iter()
+            .copied()
+            .filter(|&node| node < points.len())
+            .collect();
+        let mut lists = Self::from_subset(points, &cities, k, cost.metric());
+        for node in points.len()..cost.num_nodes() {
+            let mut list: Vec<usize> = Vec::new();
+            if cost.anchor(node).is_some() {
+                list = cities.clone();
+                let k: usize = k.min(list.len());
+                let by_cost = |a: &usize, b: &usize| {
+                    cost.distance(node, *a).total_cmp(&cost.distance(node, *b))
+                };
+                if k > 0 && k < list.len() {
+                    list.select_nth_unstable_by(k - 1, by_cost);
+                }
+                list.truncate(k);
+                list.sort_by(by_cost);
+            }
+            lists.neighbors.push(list);
+        }
+        lists
+    }
+
+    pub fn of(&self, city: usize) -> &[usize] {
+        &self.neighbors[city]
+    }
+}
@@ -1,55 +1,86 @@
-use super::tour_strategy::{CheapestInsertionStrategy, TourStrategy};
-use super::utils::{distance, two_opt};
-use geo::Point;
-use std::collections::HashSet;
+use super::hull::convex_hull;
+use super::insertion::{Candidate, InsertionTour};
+use super::path::EdgeCost;
+use super::tour_strategy::{CheapestInsertionStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{on_distinct_positions, two_opt_or_opt};
+use kiddo::{KdTree, SquaredEuclidean};
+use rayon::prelude::*;
+use std::collections::BinaryHeap;
+
+/// Number of remaining points near a newly inserted point whose cached insertion is
+/// checked against the two new tour edges.
+const UPDATE_NEIGHBORS: usize = 16;
 
 impl TourStrategy for CheapestInsertionStrategy {
-    fn build_tour(&self, points: &Vec<(f32, f32)>, hull_points: &Vec<Point<f32>>) -> Vec<usize> {
-        let mut hull_indices: Vec<usize> = Vec::new();
-        for hp in hull_points {
-            if let Some(pos) = points.iter().position(|&(x, y)| {
-                (x - hp.x()).abs() < std::f32::EPSILON && (y - hp.y()).abs() < std::f32::EPSILON
-            }) {
-                hull_indices.push(pos);
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
             }
-        }
-
-        let mut tour: Vec<usize> = hull_indices.clone();
-        let mut in_tour: HashSet<usize> = tour.iter().copied().collect();
-
-        let mut remaining: Vec<usize> =
-            (0..points.len()).filter(|i| !in_tour.contains(i)).collect();
-
-        while !remaining.is_empty() {
-            let mut best_candidate: Option<usize> = None;
-            let mut best_increase: f32 = std::f32::INFINITY;
-            let mut best_insert_position: usize = 0;
-            let mut best_candidate_idx_in_remaining: usize = 0;
-
-            for (r_idx, &candidate) in remaining.iter().enumerate() {
-                for i in 0..tour.len() {
-                    let j: usize = (i + 1) % tour.len();
-                    let cost: f32 = distance(points[tour[i]], points[candidate])
-                        + distance(points[candidate], points[tour[j]])
-                        - distance(points[tour[i]], points[tour[j]]);
-                    if cost < best_increase {
-                        best_increase = cost;
-                        best_candidate = Some(candidate);
-                        best_insert_position = j;
-                        best_candidate_idx_in_remaining = r_idx;
-                    }
+            let initial: Vec<usize> = convex_hull(points);
+            let mut tour = InsertionTour::new(EdgeCost::from_config(points, config), &initial);
+
+            let mut remaining = KdTree::<f32, 2>::with_capacity(points.len());
+            for (idx, &(x, y)) in points.iter().enumerate() {
+                if !tour.contains(idx) {
+                    remaining.add(&[x, y], idx as u64);
                 }
             }
-            if let Some(candidate) = best_candidate {
-                tour.insert(best_insert_position, candidate);
-                in_tour.insert(candidate);
-                remaining.remove(best_candidate_idx_in_remaining);
-            } else {
-                break;
+
+            // Best known insertion of every remaining point, as (cost, a, b). Heap entries
+            // whose cost no longer matches are stale and skipped.
+            let mut best: Vec<(f32, usize, usize)> = (0..points.len())
+                .into_par_iter()
+                .map(|idx| {
+                    if tour.contains(idx) {
+                        (f32::INFINITY, 0, 0)
+                    } else {
+                        tour.best_insertion(idx)
+                    }
+                })
+                .collect();
+            let mut heap: BinaryHeap<Candidate> = (0..points.len())
+                .filter(|&idx| !tour.contains(idx))
+                .map(|idx| Candidate {
+                    cost: best[idx].0,
+                    point: idx,
+                })
+                .collect();
+
+            while let Some(Candidate { cost, point: p }) = heap.pop() {
+                if tour.contains(p) || cost != best[p].0 {
+                    continue;
+                }
+                let (_, a, b) = best[p];
+                if !tour.has_edge(a, b) {
+                    // The cached edge was split by an earlier insertion.
+                    best[p] = tour.best_insertion(p);
+                    heap.push(Candidate {
+                        cost: best[p].0,
+                        point: p,
+                    });
+                    continue;
+                }
+
+                tour.insert(p, a, b);
+                remaining.remove(&[points[p].0, points[p].1], p as u64);
+
+                let (x, y) = points[p];
+                for neighbor in remaining.nearest_n::<SquaredEuclidean>(&[x, y], UPDATE_NEIGHBORS) {
+                    let q: usize = neighbor.item as usize;
+                    for (u, v) in [(a, p), (p, b)] {
+                        let cost: f32 = tour.insertion_cost(q, u, v);
+                        if cost < best[q].0 {
+                            best[q] = (cost, u, v);
+                            heap.push(Candidate { cost, point: q });
+                        }
+                    }
+                }
             }
-        }
 
-        two_opt(&points, &mut tour);
-        tour
+            let mut tour: Vec<usize> = tour.order_from(initial[0]);
+            two_opt_or_opt(points, &mut tour, config);
+            Ok(Tour::new(tour))
+        })
     }
 }
@@ -0,0 +1,52 @@
+use super::hull::convex_hull;
+use super::insertion::{Candidate, InsertionTour};
+use super::path::EdgeCost;
+use super::tour_strategy::{FarthestInsertionStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{on_distinct_positions, two_opt_or_opt};
+use rayon::prelude::*;
+use std::collections::BinaryHeap;
+
+impl TourStrategy for FarthestInsertionStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+            let initial: Vec<usize> = convex_hull(points);
+            let mut tour = InsertionTour::new(EdgeCost::from_config(points, config), &initial);
+
+            // Keyed by the negated distance to the tour so the farthest point pops first.
+            // Distances only shrink as the tour grows, so a cached entry is an upper bound
+            // and is refreshed when popped.
+            let mut heap: BinaryHeap<Candidate> = (0..points.len())
+                .into_par_iter()
+                .filter(|&idx| !tour.contains(idx))
+                .map(|idx| Candidate {
+                    cost: -tour.nearest_distance(idx),
+                    point: idx,
+                })
+                .collect::<Vec<Candidate>>()
+                .into();
+
+            while let Some(Candidate { cost, point: p }) = heap.pop() {
+                if tour.contains(p) {
+                    continue;
+                }
+                let distance: f32 = tour.nearest_distance(p);
+                if distance < -cost {
+                    heap.push(Candidate {
+                        cost: -distance,
+                        point: p,
+                    });
+                    continue;
+                }
+                let (_, a, b) = tour.best_insertion(p);
+                tour.insert(p, a, b);
+            }
+
+            let mut tour: Vec<usize> = tour.order_from(initial[0]);
+            two_opt_or_opt(points, &mut tour, config);
+            Ok(Tour::new(tour))
+        })
+    }
+}
@@ -1,113 +1,157 @@
-use super::tour_strategy::{GreedyStrategy, TourStrategy};
-use super::utils::{distance, two_opt};
-use geo::Point;
+use super::hull::convex_hull;
+use super::path::EdgeCost;
+use super::tour_strategy::{GreedyStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{on_distinct_positions, remaining, two_opt, two_opt_or_opt};
 use kiddo::{KdTree, SquaredEuclidean};
+use std::time::Instant;
 
 impl TourStrategy for GreedyStrategy {
-    fn build_tour(&self, points: &Vec<(f32, f32)>, hull_points: &Vec<Point<f32>>) -> Vec<usize> {
-        let mut hull_indices: Vec<usize> = hull_points
-            .into_iter()
-            .map(|p| {
-                points
-                    .iter()
-                    .position(|&pt| pt.0 == p.x() && pt.1 == p.y())
-                    .unwrap()
-            })
-            .collect();
-
-        if hull_indices.len() > 1 && hull_indices.first() == hull_indices.last() {
-            hull_indices.pop();
-        }
-        let mut tour: Vec<usize> = hull_indices.clone();
-        let mut in_tour: Vec<bool> = vec![false; points.len()];
-        for &idx in &tour {
-            in_tour[idx] = true;
-        }
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+            // The construction with its 2-opt passes gets half of the time budget and
+            // the final local search the rest.
+            let start: Instant = Instant::now();
+            let deadline: Option<Instant> = config.time_budget.map(|budget| start + budget);
+            let construction: Option<Instant> = config.time_budget.map(|budget| start + budget / 2);
+            let budgeted = |deadline: Option<Instant>| TourConfig {
+                time_budget: remaining(deadline),
+                ..config.clone()
+            };
+            let cost = EdgeCost::from_config(points, config);
+            let mut tour: Vec<usize> = convex_hull(points);
+            let mut in_tour: Vec<bool> = vec![false; points.len()];
+            for &idx in &tour {
+                in_tour[idx] = true;
+            }
 
-        let mut kdtree = KdTree::<f32, 2>::with_capacity(points.len());
-        for (idx, &(x, y)) in points.iter().enumerate() {
-            if !in_tour[idx] {
-                kdtree.add(&[x, y], idx as u64);
+            let mut kdtree = KdTree::<f32, 2>::with_capacity(points.len());
+            for (idx, &(x, y)) in points.iter().enumerate() {
+                if !in_tour[idx] {
+                    kdtree.add(&[x, y], idx as u64);
+                }
             }
-        }
 
-        while tour.len() < points.len() {
-            let mut best_increase: f32 = f32::INFINITY;
-            let mut best_p = None;
-            let mut best_k = None;
-
-            let step: usize = (tour.len() >> 8).max(1).min(4);
-            let max_neighbors: usize = if step > 4 { 8 } else { 3 };
-            for k in (0..tour.len()).step_by(step) {
-                let next_k: usize = (k + 1) % tour.len();
-                let i: usize = tour[k];
-                let j: usize = tour[next_k];
-                let p1: (f32, f32) = points[i];
-                let p2: (f32, f32) = points[j];
-
-                let mid_x: f32 = (p1.0 + p2.0) / 2.0;
-                let mid_y: f32 = (p1.1 + p2.1) / 2.0;
-
-                let neighbors =
-                    kdtree.nearest_n::<SquaredEuclidean>(&[mid_x, mid_y], max_neighbors);
-                for neighbor in neighbors {
-                    let p: usize = neighbor.item as usize;
-                    if in_tour[p] {
-                        continue;
+            while tour.len() < points.len() {
+                if construction.is_some_and(|construction| Instant::now() >= construction) {
+                    insert_next_to_nearest(&cost, &mut tour, &in_tour);
+                    break;
+                }
+                let mut best_increase: f32 = f32::INFINITY;
+                let mut best_p = None;
+                let mut best_k = None;
+
+                let step: usize = (tour.len() >> 8).clamp(1, 4);
+                let max_neighbors: usize = if step > 4 { 8 } else { 3 };
+                for k in (0..tour.len()).step_by(step) {
+                    let next_k: usize = (k + 1) % tour.len();
+                    let i: usize = tour[k];
+                    let j: usize = tour[next_k];
+                    let p1: (f32, f32) = points[i];
+                    let p2: (f32, f32) = points[j];
+
+                    let mid_x: f32 = (p1.0 + p2.0) / 2.0;
+                    let mid_y: f32 = (p1.1 + p2.1) / 2.0;
+
+                    let neighbors =
+                        kdtree.nearest_n::<SquaredEuclidean>(&[mid_x, mid_y], max_neighbors);
+                    for neighbor in neighbors {
+                        let p: usize = neighbor.item as usize;
+                        if in_tour[p] {
+                            continue;
+                        }
+
+                        let increase: f32 =
+                            cost.distance(i, p) + cost.distance(p, j) - cost.distance(i, j);
+
+                        if increase < best_increase {
+                            best_increase = increase;
+                            best_p = Some(p);
+                            best_k = Some(k);
+                        }
                     }
+                }
 
-                    let increase: f32 = distance(points[i], points[p])
-                        + distance(points[p], points[j])
-                        - distance(points[i], points[j]);
+                if let (Some(p), Some(k)) = (best_p, best_k) {
+                    let next_k: usize = (k + 1) % tour.len();
 
-                    if increase < best_increase {
-                        best_increase = increase;
-                        best_p = Some(p);
-                        best_k = Some(k);
+                    if next_k == 0 {
+                        tour.push(p);
+                    } else {
+                        tour.insert(next_k, p);
                     }
-                }
-            }
 
-            if let (Some(p), Some(k)) = (best_p, best_k) {
-                let next_k: usize = (k + 1) % tour.len();
+                    in_tour[p] = true;
 
-                if next_k == 0 {
-                    tour.push(p);
+                    kdtree.remove(&[points[p].0, points[p].1], p as u64);
                 } else {
-                    tour.insert(next_k, p);
-                }
+                    let remaining: Vec<usize> =
+                        (0..points.len()).filter(|&idx| !in_tour[idx]).collect();
 
-                in_tour[p] = true;
+                    if !remaining.is_empty() {
+                        let p: usize = remaining[0];
+                        tour.push(p);
+                        in_tour[p] = true;
 
-                let neighbors =
-                    kdtree.nearest_n::<SquaredEuclidean>(&[points[p].0, points[p].1], 1);
-                for neighbor in neighbors.iter() {
-                    let idx = neighbor.item as usize;
-                    if idx == p {
                         kdtree.remove(&[points[p].0, points[p].1], p as u64);
+                    } else {
                         break;
                     }
                 }
-            } else {
-                let remaining: Vec<usize> =
-                    (0..points.len()).filter(|&idx| !in_tour[idx]).collect();
-
-                if !remaining.is_empty() {
-                    let p: usize = remaining[0];
-                    tour.push(p);
-                    in_tour[p] = true;
-
-                    kdtree.remove(&[points[p].0, points[p].1], p as u64);
-                } else {
-                    break;
+                if tour.len() >= 64 && tour.len().is_power_of_two() {
+                    two_opt(points, &mut tour, &budgeted(construction));
                 }
             }
-            if tour.len() % 32 == 0 {
-                two_opt(&points, &mut tour);
-            }
-        }
 
-        two_opt(&points, &mut tour);
-        tour
+            two_opt_or_opt(points, &mut tour, &budgeted(deadline));
+            Ok(Tour::new(tour))
+        })
+    }
+}
+
+/// Inserts every point not `in_tour` next to the nearest point of the closed `tour`,
+/// on the cheaper side, in O(n log n). Finishes the construction once the time
+/// budget is spent, as every greedy insertion scans the whole tour.
+fn insert_next_to_nearest(cost: &EdgeCost, tour: &mut Vec<usize>, in_tour: &[bool]) {
+    let points: &[(f32, f32)] = cost.points();
+    let n: usize = points.len();
+    let mut next: Vec<usize> = vec![usize::MAX; n];
+    let mut prev: Vec<usize> = vec![usize::MAX; n];
+    let mut kdtree = KdTree::<f32, 2>::with_capacity(n);
+    for (k, &i) in tour.iter().enumerate() {
+        next[i] = tour[(k + 1) % tour.len()];
+        prev[next[i]] = i;
+        kdtree.add(&[points[i].0, points[i].1], i as u64);
+    }
+
+    for p in (0..n).filter(|&p| !in_tour[p]) {
+        let t: usize = kdtree
+            .nearest_one::<SquaredEuclidean>(&[points[p].0, points[p].1])
+            .item as usize;
+        let after =
+            |i: usize, j: usize| cost.distance(i, p) + cost.distance(p, j) - cost.distance(i, j);
+        let (i, j) = if after(prev[t], t) < after(t, next[t]) {
+            (prev[t], t)
+        } else {
+            (t, next[t])
+        };
+        next[i] = p;
+        prev[p] = i;
+        next[p] = j;
+        prev[j] = p;
+        kdtree.add(&[points[p].0, points[p].1], p as u64);
+    }
+
+    let first: usize = tour[0];
+    tour.clear();
+    let mut i: usize = first;
+    loop {
+        tour.push(i);
+        i = next[i];
+        if i == first {
+            break;
+        }
     }
 }
@@ -0,0 +1,118 @@
+use super::candidates::CandidateLists;
+use super::path::EdgeCost;
+use super::tour_strategy::{GreedyEdgeStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{find, on_distinct_positions, two_opt_or_opt};
+use kiddo::{KdTree, SquaredEuclidean};
+use rayon::prelude::*;
+
+/// Number of nearest neighbors per point whose edges are considered for matching.
+const CANDIDATE_EDGES: usize = 10;
+
+const NO_NEIGHBOR: usize = usize::MAX;
+
+impl TourStrategy for GreedyEdgeStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+            let n: usize = points.len();
+            if n <= 3 {
+                let mut tour: Vec<usize> = (0..n).collect();
+                EdgeCost::from_config(points, config).cut(&mut tour);
+                return Ok(Tour::new(tour));
+            }
+
+            let candidates = CandidateLists::new(points, CANDIDATE_EDGES, config.metric);
+            let cost = EdgeCost::from_config(points, config);
+            let mut pairs: Vec<(usize, usize)> = (0..n)
+                .flat_map(|a| candidates.of(a).iter().map(move |&b| (a.min(b), a.max(b))))
+                .collect();
+            pairs.par_sort_unstable();
+            pairs.dedup();
+            let mut edges: Vec<(f32, usize, usize)> = pairs
+                .into_par_iter()
+                .map(|(a, b)| (cost.distance(a, b), a, b))
+                .collect();
+            edges.par_sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+            // Add the shortest edges that keep every point at degree <= 2 without
+            // closing a cycle, which leaves a set of path fragments.
+            let mut parent: Vec<usize> = (0..n).collect();
+            let mut adjacent: Vec<[usize; 2]> = vec![[NO_NEIGHBOR; 2]; n];
+            for (_, a, b) in edges {
+                if adjacent[a][1] != NO_NEIGHBOR || adjacent[b][1] != NO_NEIGHBOR {
+                    continue;
+                }
+                let root_a: usize = find(&mut parent, a);
+                let root_b: usize = find(&mut parent, b);
+                if root_a == root_b {
+                    continue;
+                }
+                parent[root_a] = root_b;
+                link(&mut adjacent, a, b);
+                link(&mut adjacent, b, a);
+            }
+
+            // Chain the fragments, always jumping to the nearest free fragment end.
+            let mut endpoints = KdTree::<f32, 2>::with_capacity(n);
+            for (idx, &(x, y)) in points.iter().enumerate() {
+                if adjacent[idx][1] == NO_NEIGHBOR {
+                    endpoints.add(&[x, y], idx as u64);
+                }
+            }
+
+            let mut tour: Vec<usize> = Vec::with_capacity(n);
+            let mut start: usize = (0..n)
+                .find(|&idx| adjacent[idx][1] == NO_NEIGHBOR)
+                .unwrap_or(0);
+            loop {
+                remove_endpoint(&mut endpoints, points, start);
+                let end: usize = walk_fragment(&adjacent, start, &mut tour);
+                if end != start {
+                    remove_endpoint(&mut endpoints, points, end);
+                }
+                if tour.len() >= n {
+                    break;
+                }
+                let (x, y) = points[end];
+                start = endpoints.nearest_one::<SquaredEuclidean>(&[x, y]).item as usize;
+            }
+
+            two_opt_or_opt(points, &mut tour, config);
+            Ok(Tour::new(tour))
+        })
+    }
+}
+
+fn link(adjacent: &mut [[usize; 2]], from: usize, to: usize) {
+    if adjacent[from][0] == NO_NEIGHBOR {
+        adjacent[from][0] = to;
+    } else {
+        adjacent[from][1] = to;
+    }
+}
+
+fn remove_endpoint(endpoints: &mut KdTree<f32, 2>, points: &[(f32, f32)], idx: usize) {
+    endpoints.remove(&[points[idx].0, points[idx].1], idx as u64);
+}
+
+/// Appends the fragment starting at the free end `start` to `tour` and returns the
+/// point at its other end.
+fn walk_fragment(adjacent: &[[usize; 2]], start: usize, tour: &mut Vec<usize>) -> usize {
+    let mut previous: usize = NO_NEIGHBOR;
+    let mut current: usize = start;
+    loop {
+        tour.push(current);
+        let next: usize = if adjacent[current][0] != previous {
+            adjacent[current][0]
+        } else {
+            adjacent[current][1]
+        };
+        if next == NO_NEIGHBOR {
+            return current;
+        }
+        previous = current;
+        current = next;
+    }
+}
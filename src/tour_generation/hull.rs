@@ -0,0 +1,53 @@
+use std::cmp::Ordering;
+
+/// Indices of the convex hull vertices of `points` in counter-clockwise order,
+/// computed with Andrew's monotone chain in O(n log n).
+///
+/// Points on a hull edge are not vertices, and of several points at the same
+/// position only the one with the lowest index is used. Collinear points give the
+/// two ends of their segment, and points that all coincide give a single index.
+pub fn convex_hull(points: &[(f32, f32)]) -> Vec<usize> {
+    let mut sorted: Vec<usize> = (0..points.len()).collect();
+    sorted.sort_by(|&a, &b| compare(points[a], points[b]).then(a.cmp(&b)));
+    sorted.dedup_by(|a, b| points[*a] == points[*b]);
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // Lower chain from left to right, then the upper chain back, both keeping only
+    // strict left turns.
+    let mut hull: Vec<usize> = Vec::with_capacity(sorted.len() + 1);
+    for &idx in &sorted {
+        while hull.len() >= 2 && turn(points, &hull, idx) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(idx);
+    }
+    let lower_len: usize = hull.len() + 1;
+    for &idx in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len && turn(points, &hull, idx) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(idx);
+    }
+    // The upper chain ends at the first point again.
+    hull.pop();
+    hull
+}
+
+/// Orders by x, then y. Adding zero turns -0.0 into 0.0, so that equal positions
+/// end up next to each other.
+pub fn compare(a: (f32, f32), b: (f32, f32)) -> Ordering {
+    (a.0 + 0.0)
+        .total_cmp(&(b.0 + 0.0))
+        .then((a.1 + 0.0).total_cmp(&(b.1 + 0.0)))
+}
+
+/// Twice the signed area of the last two hull points and `idx`, positive for a left
+/// turn.
+fn turn(points: &[(f32, f32)], hull: &[usize], idx: usize) -> f32 {
+    let o: (f32, f32) = points[hull[hull.len() - 2]];
+    let a: (f32, f32) = points[hull[hull.len() - 1]];
+    let b: (f32, f32) = points[idx];
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
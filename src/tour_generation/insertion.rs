@@ -0,0 +1,150 @@
+use super::path::EdgeCost;
+use kiddo::{KdTree, SquaredEuclidean};
+use std::cmp::Ordering;
+
+/// Number of tour points closest to a candidate whose edges are considered when
+/// looking for its cheapest insertion.
+const NEAREST_TOUR_POINTS: usize = 8;
+
+const NOT_IN_TOUR: usize = usize::MAX;
+
+/// Heap entry ordered so that the lowest cost is popped first.
+pub struct Candidate {
+    pub cost: f32,
+    pub point: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.point.cmp(&self.point))
+    }
+}
+
+/// Partial tour for insertion heuristics. The tour is a doubly linked list, so
+/// inserting a point is O(1), and a kd-tree over the tour points restricts the search
+/// for the cheapest edge to insert a point into to the edges around its nearest tour
+/// points.
+pub struct InsertionTour<'a> {
+    points: &'a [(f32, f32)],
+    cost: EdgeCost<'a>,
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    kdtree: KdTree<f32, 2>,
+    len: usize,
+}
+
+impl<'a> InsertionTour<'a> {
+    /// Starts from the closed tour visiting `initial` in order, which must not be empty.
+    pub fn new(cost: EdgeCost<'a>, initial: &[usize]) -> Self {
+        let points: &'a [(f32, f32)] = cost.points();
+        let mut tour = Self {
+            points,
+            cost,
+            next: vec![NOT_IN_TOUR; points.len()],
+            prev: vec![NOT_IN_TOUR; points.len()],
+            kdtree: KdTree::<f32, 2>::with_capacity(points.len()),
+            len: 0,
+        };
+        for (i, &idx) in initial.iter().enumerate() {
+            let next_idx: usize = initial[(i + 1) % initial.len()];
+            tour.next[idx] = next_idx;
+            tour.prev[next_idx] = idx;
+            tour.kdtree.add(&[points[idx].0, points[idx].1], idx as u64);
+        }
+        tour.len = initial.len();
+        tour
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.next[idx] != NOT_IN_TOUR
+    }
+
+    /// True if `b` directly follows `a` in the tour.
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.next[a] == b
+    }
+
+    /// Squared Euclidean distance from `p` to the closest tour point.
+    pub fn nearest_distance(&self, p: usize) -> f32 {
+        let (x, y) = self.points[p];
+        self.kdtree
+            .nearest_one::<SquaredEuclidean>(&[x, y])
+            .distance
+    }
+
+    /// Increase in tour length when `p` is inserted into the edge (a, b).
+    pub fn insertion_cost(&self, p: usize, a: usize, b: usize) -> f32 {
+        self.cost.distance(a, p) + self.cost.distance(p, b) - self.cost.distance(a, b)
+    }
+
+    /// Cheapest edge (a, b) to insert `p` into among the edges touching the tour points
+    /// nearest to `p`, as (cost, a, b).
+    pub fn best_insertion(&self, p: usize) -> (f32, usize, usize) {
+        let (x, y) = self.points[p];
+        let mut best: (f32, usize, usize) = (f32::INFINITY, NOT_IN_TOUR, NOT_IN_TOUR);
+        for neighbor in self
+            .kdtree
+            .nearest_n::<SquaredEuclidean>(&[x, y], NEAREST_TOUR_POINTS)
+        {
+            let t: usize = neighbor.item as usize;
+            for (a, b) in [(self.prev[t], t), (t, self.next[t])] {
+                let cost: f32 = self.insertion_cost(p, a, b);
+                if cost < best.0 {
+                    best = (cost, a, b);
+                }
+            }
+        }
+        best
+    }
+
+    /// Inserts `p` into the edge (a, b), which must be part of the tour.
+    pub fn insert(&mut self, p: usize, a: usize, b: usize) {
+        debug_assert!(self.has_edge(a, b));
+        self.next[a] = p;
+        self.prev[p] = a;
+        self.next[p] = b;
+        self.prev[b] = p;
+        self.kdtree
+            .add(&[self.points[p].0, self.points[p].1], p as u64);
+        self.len += 1;
+    }
+
+    /// Tour order starting from `start`, which must be in the tour.
+    pub fn order_from(&self, start: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = Vec::with_capacity(self.len);
+        let mut current: usize = start;
+        loop {
+            order.push(current);
+            current = self.next[current];
+            if current == start {
+                break;
+            }
+        }
+        order
+    }
+}
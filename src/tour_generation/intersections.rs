@@ -0,0 +1,428 @@
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+/// True if the segments (a, b) and (c, d) share at least one point.
+pub fn segments_intersect(a: (f32, f32), b: (f32, f32), c: (f32, f32), d: (f32, f32)) -> bool {
+    let o1: f64 = orientation(a, b, c);
+    let o2: f64 = orientation(a, b, d);
+    let o3: f64 = orientation(c, d, a);
+    let o4: f64 = orientation(c, d, b);
+
+    if ((o1 > 0.0 && o2 < 0.0) || (o1 < 0.0 && o2 > 0.0))
+        && ((o3 > 0.0 && o4 < 0.0) || (o3 < 0.0 && o4 > 0.0))
+    {
+        return true;
+    }
+    (o1 == 0.0 && on_segment(a, b, c))
+        || (o2 == 0.0 && on_segment(a, b, d))
+        || (o3 == 0.0 && on_segment(c, d, a))
+        || (o4 == 0.0 && on_segment(c, d, b))
+}
+
+/// True if tour edges `i` and `j` intersect anywhere other than at the point that
+/// adjacent edges share. Adjacent edges that fold back onto each other intersect. The
+/// tour must not visit the same position twice in a row, see [`collapse`].
+fn edges_intersect(points: &[(f32, f32)], tour: &[usize], i: usize, j: usize) -> bool {
+    let n: usize = tour.len();
+    let (a, b) = (points[tour[i]], points[tour[(i + 1) % n]]);
+    let (c, d) = (points[tour[j]], points[tour[(j + 1) % n]]);
+    let (shared, u, v) = if (i + 1) % n == j {
+        (b, a, d)
+    } else if (j + 1) % n == i {
+        (a, b, c)
+    } else {
+        return segments_intersect(a, b, c, d);
+    };
+    let dot: f64 = (u.0 as f64 - shared.0 as f64) * (v.0 as f64 - shared.0 as f64)
+        + (u.1 as f64 - shared.1 as f64) * (v.1 as f64 - shared.1 as f64);
+    orientation(shared, u, v) == 0.0 && dot > 0.0
+}
+
+/// Twice the signed area of the triangle (a, b, c), positive when counterclockwise.
+fn orientation(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f64 {
+    let (ax, ay) = (a.0 as f64, a.1 as f64);
+    (b.0 as f64 - ax) * (c.1 as f64 - ay) - (b.1 as f64 - ay) * (c.0 as f64 - ax)
+}
+
+/// True if `p`, known to be collinear with (a, b), lies within the segment.
+fn on_segment(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> bool {
+    p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0) && p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
+
+/// Number of pairs of tour edges that intersect, see [`find_intersection`].
+pub fn count_self_intersections(points: &[(f32, f32)], tour: &[usize], closed: bool) -> usize {
+    intersecting_edges(points, tour, closed).len()
+}
+
+/// Number of edges of a tour of `len` points, which for an open path leaves out the
+/// edge back to the first point.
+fn num_edges(len: usize, closed: bool) -> usize {
+    if closed { len } else { len.saturating_sub(1) }
+}
+
+/// The tour with every run of consecutive points at the same position collapsed into
+/// one vertex, since stipple points often share a position and the zero-length edges
+/// between them touch nothing. Returns the positions in `tour` where the runs start,
+/// the last one wrapping around to the first for a closed tour.
+pub fn collapse(points: &[(f32, f32)], tour: &[usize], closed: bool) -> Vec<usize> {
+    let n: usize = tour.len();
+    let runs: Vec<usize> = (0..n)
+        .filter(|&i| (i == 0 && !closed) || points[tour[i]] != points[tour[(i + n - 1) % n]])
+        .collect();
+    if runs.is_empty() && n > 0 {
+        return vec![0];
+    }
+    runs
+}
+
+/// Runs `find` on the tour with coincident consecutive points collapsed, see
+/// [`collapse`], and maps the edges it reports back to positions in `tour`.
+fn on_collapsed(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    closed: bool,
+    find: impl FnOnce(&[usize]) -> Vec<(usize, usize)>,
+) -> Vec<(usize, usize)> {
+    let n: usize = tour.len();
+    let runs: Vec<usize> = collapse(points, tour, closed);
+    let m: usize = runs.len();
+    let collapsed: Vec<usize> = runs.iter().map(|&i| tour[i]).collect();
+    // The collapsed edge k is the edge of `tour` that enters run k + 1.
+    let edge = |k: usize| (runs[(k + 1) % m] + n - 1) % n;
+    let mut pairs: Vec<(usize, usize)> = find(&collapsed)
+        .into_iter()
+        .map(|(i, j)| (edge(i).min(edge(j)), edge(i).max(edge(j))))
+        .collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Every pair of tour edges that intersect, as the positions in `tour`
+/// of their first points. Consecutive points at the same position count as one
+/// vertex, so the zero-length edges between them are never reported.
+///
+/// Edges are bucketed into a uniform grid sized after the mean edge length, and only
+/// edges sharing a cell are tested. A pair is counted in the cell holding the corner
+/// of the overlap of their bounding boxes, so it is counted once.
+pub fn intersecting_edges(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    closed: bool,
+) -> Vec<(usize, usize)> {
+    on_collapsed(points, tour, closed, |tour| {
+        grid_intersections(points, tour, closed)
+    })
+}
+
+fn grid_intersections(points: &[(f32, f32)], tour: &[usize], closed: bool) -> Vec<(usize, usize)> {
+    let n: usize = tour.len();
+    let edges: usize = num_edges(n, closed);
+    if edges < 2 {
+        return Vec::new();
+    }
+    let edge = |i: usize| (points[tour[i]], points[tour[(i + 1) % n]]);
+
+    let (mut min, mut max) = (
+        (f32::INFINITY, f32::INFINITY),
+        (f32::NEG_INFINITY, f32::NEG_INFINITY),
+    );
+    for &idx in tour {
+        let (x, y) = points[idx];
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+    let total: f32 = (0..edges)
+        .map(|i| {
+            let (a, b) = edge(i);
+            (b.0 - a.0).abs().max((b.1 - a.1).abs())
+        })
+        .sum();
+    // Keep the number of cells in the order of the number of edges.
+    let area_cell: f32 = ((max.0 - min.0) * (max.1 - min.1) / edges as f32).sqrt();
+    let cell_size: f32 = (total / edges as f32).max(area_cell).max(f32::EPSILON);
+    let columns: usize = ((max.0 - min.0) / cell_size) as usize + 1;
+    let rows: usize = ((max.1 - min.1) / cell_size) as usize + 1;
+    let cell = |p: (f32, f32)| -> (usize, usize) {
+        (
+            (((p.0 - min.0) / cell_size) as usize).min(columns - 1),
+            (((p.1 - min.1) / cell_size) as usize).min(rows - 1),
+        )
+    };
+    let cell_range = |i: usize| {
+        let (a, b) = edge(i);
+        (
+            cell((a.0.min(b.0), a.1.min(b.1))),
+            cell((a.0.max(b.0), a.1.max(b.1))),
+        )
+    };
+
+    // Edges of cell k are cell_edges[offsets[k]..offsets[k + 1]].
+    let mut offsets: Vec<usize> = vec![0; columns * rows + 1];
+    for i in 0..edges {
+        let (lo, hi) = cell_range(i);
+        for y in lo.1..=hi.1 {
+            for x in lo.0..=hi.0 {
+                offsets[y * columns + x + 1] += 1;
+            }
+        }
+    }
+    for k in 1..offsets.len() {
+        offsets[k] += offsets[k - 1];
+    }
+    let mut fill: Vec<usize> = offsets.clone();
+    let mut cell_edges: Vec<usize> = vec![0; offsets[columns * rows]];
+    for i in 0..edges {
+        let (lo, hi) = cell_range(i);
+        for y in lo.1..=hi.1 {
+            for x in lo.0..=hi.0 {
+                cell_edges[fill[y * columns + x]] = i;
+                fill[y * columns + x] += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (0..columns * rows)
+        .into_par_iter()
+        .flat_map_iter(|k| {
+            let in_cell: &[usize] = &cell_edges[offsets[k]..offsets[k + 1]];
+            let mut found: Vec<(usize, usize)> = Vec::new();
+            for (m, &i) in in_cell.iter().enumerate() {
+                let (a, b) = edge(i);
+                for &j in &in_cell[m + 1..] {
+                    let (c, d) = edge(j);
+                    let corner: (f32, f32) = (
+                        a.0.min(b.0).max(c.0.min(d.0)),
+                        a.1.min(b.1).max(c.1.min(d.1)),
+                    );
+                    if cell(corner) == (k % columns, k / columns)
+                        && edges_intersect(points, tour, i, j)
+                    {
+                        found.push((i.min(j), i.max(j)));
+                    }
+                }
+            }
+            found
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// True if the tour never touches itself, i.e. it is a simple polygon, or a simple
+/// polyline when it is an open path.
+pub fn is_simple(points: &[(f32, f32)], tour: &[usize], closed: bool) -> bool {
+    find_intersection(points, tour, closed).is_none()
+}
+
+/// Finds a pair of tour edges that intersect, as the positions in `tour` of their
+/// first points, or None if the tour is a simple polygon. Adjacent edges only count
+/// when they overlap beyond their shared point, and consecutive points at the same
+/// position count as one vertex.
+///
+/// Shamos–Hoey sweep line: edges are kept ordered from bottom to top in a search tree
+/// while a vertical line sweeps from left to right, and only edges that become
+/// neighbors in that order are tested. The first intersection is always found between
+/// neighbors, so this runs in O(n log n).
+pub fn find_intersection(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    closed: bool,
+) -> Option<(usize, usize)> {
+    on_collapsed(points, tour, closed, |tour| {
+        sweep_intersection(points, tour, closed)
+            .into_iter()
+            .collect()
+    })
+    .pop()
+}
+
+fn sweep_intersection(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    closed: bool,
+) -> Option<(usize, usize)> {
+    let n: usize = tour.len();
+    let edges: usize = num_edges(n, closed);
+    if edges < 2 {
+        return None;
+    }
+    // Edge i runs between its left and right endpoints in (x, y) order.
+    let segments: Vec<Segment> = (0..edges)
+        .map(|i| {
+            let (a, b) = (points[tour[i]], points[tour[(i + 1) % n]]);
+            if point_order(a, b) != Ordering::Greater {
+                (a, b)
+            } else {
+                (b, a)
+            }
+        })
+        .collect();
+
+    // (point, is_end, edge), with insertions before removals at the same point.
+    let mut events: Vec<((f32, f32), bool, usize)> = Vec::with_capacity(2 * edges);
+    for (i, &(left, right)) in segments.iter().enumerate() {
+        events.push((left, false, i));
+        events.push((right, true, i));
+    }
+    events.sort_unstable_by(|a, b| point_order(a.0, b.0).then(a.1.cmp(&b.1)));
+
+    let check = |i: usize, j: usize| -> Option<(usize, usize)> {
+        if edges_intersect(points, tour, i, j) {
+            Some((i.min(j), i.max(j)))
+        } else {
+            None
+        }
+    };
+
+    let mut status: BTreeSet<Active> = BTreeSet::new();
+    for (_, is_end, s) in events {
+        let active = Active {
+            edge: s,
+            segments: &segments,
+        };
+        if is_end {
+            if !status.remove(&active) {
+                continue;
+            }
+            let below = status.range(..&active).next_back();
+            let above = status
+                .range((Bound::Excluded(&active), Bound::Unbounded))
+                .next();
+            if let (Some(below), Some(above)) = (below, above)
+                && let Some(pair) = check(below.edge, above.edge)
+            {
+                return Some(pair);
+            }
+        } else {
+            if let Some(below) = status.range(..&active).next_back()
+                && let Some(pair) = check(below.edge, s)
+            {
+                return Some(pair);
+            }
+            if let Some(above) = status
+                .range((Bound::Excluded(&active), Bound::Unbounded))
+                .next()
+                && let Some(pair) = check(s, above.edge)
+            {
+                return Some(pair);
+            }
+            status.insert(active);
+        }
+    }
+    None
+}
+
+/// Edge from its left to its right endpoint.
+type Segment = ((f32, f32), (f32, f32));
+
+/// Edge in the sweep status of [`find_intersection`], ordered from bottom to top
+/// where the sweep line crosses it. Two edges keep their order for as long as both are
+/// crossed by the sweep line, unless they intersect, so the order is consistent up
+/// to the first intersection.
+struct Active<'a> {
+    edge: usize,
+    segments: &'a [Segment],
+}
+
+impl Ord for Active<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (s, t) = (self.segments[self.edge], self.segments[other.edge]);
+        // Compare at the left end the sweep line reached last.
+        let ordering: Ordering = if point_order(s.0, t.0) == Ordering::Greater {
+            vertical_order(t, s).reverse()
+        } else {
+            vertical_order(s, t)
+        };
+        ordering.then(self.edge.cmp(&other.edge))
+    }
+}
+
+impl PartialOrd for Active<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Active<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Active<'_> {}
+
+/// Less if segment `t` passes below the left end of segment `s`, or through it and
+/// below the rest of `s`, Greater if it passes above, and Equal if both lie on the
+/// same line.
+fn vertical_order(t: Segment, s: Segment) -> Ordering {
+    let ((tl, tr), (sl, sr)) = (t, s);
+    let mut o: f64 = orientation(tl, tr, sl);
+    if o == 0.0 {
+        o = orientation(tl, tr, sr);
+    }
+    0.0.partial_cmp(&o).unwrap_or(Ordering::Equal)
+}
+
+/// Orders by x, then y, the order in which the sweep line reaches points.
+fn point_order(a: (f32, f32), b: (f32, f32)) -> Ordering {
+    a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn sweep_agrees_with_grid_on_small_random_tours() {
+        let mut rng: StdRng = StdRng::seed_from_u64(1);
+        let mut simple: usize = 0;
+        for _ in 0..5000 {
+            let n: usize = rng.random_range(3..9);
+            // A coarse grid makes collinear and touching edges common.
+            let points: Vec<(f32, f32)> = (0..n)
+                .map(|_| (rng.random_range(0..6) as f32, rng.random_range(0..6) as f32))
+                .collect();
+            let mut tour: Vec<usize> = (0..n).collect();
+            tour.shuffle(&mut rng);
+            for closed in [true, false] {
+                let grid: bool = intersecting_edges(&points, &tour, closed).is_empty();
+                assert_eq!(is_simple(&points, &tour, closed), grid, "{:?}", points);
+                simple += grid as usize;
+            }
+        }
+        assert!(simple > 1000);
+    }
+
+    #[test]
+    fn coincident_consecutive_points_count_as_one_vertex() {
+        // A square that visits each corner twice, and once three times around the wrap.
+        let points: Vec<(f32, f32)> = [(0, 0), (0, 0), (4, 0), (4, 0), (4, 4), (0, 4), (0, 0)]
+            .iter()
+            .map(|&(x, y)| (x as f32, y as f32))
+            .collect();
+        let tour: Vec<usize> = (0..points.len()).collect();
+        assert_eq!(intersecting_edges(&points, &tour, true), Vec::new());
+        assert!(is_simple(&points, &tour, true));
+        assert_eq!(collapse(&points, &tour, true), vec![2, 4, 5, 6]);
+        // The open path ends before it comes back to its first corner.
+        assert_eq!(intersecting_edges(&points, &tour[..6], false), Vec::new());
+        assert!(is_simple(&points, &tour[..6], false));
+        assert_eq!(collapse(&points, &tour[..6], false), vec![0, 2, 4, 5]);
+
+        // Coming back to a position later still touches the tour there, and the
+        // reported edges are the ones of `tour` that meet at it.
+        let points: Vec<(f32, f32)> = [(0, 0), (2, 2), (2, 2), (4, 0), (4, 4), (2, 2), (0, 4)]
+            .iter()
+            .map(|&(x, y)| (x as f32, y as f32))
+            .collect();
+        let tour: Vec<usize> = (0..points.len()).collect();
+        let pairs: Vec<(usize, usize)> = intersecting_edges(&points, &tour, true);
+        assert!(pairs.contains(&(0, 4)), "{:?}", pairs);
+        assert!(find_intersection(&points, &tour, true).is_some());
+    }
+}
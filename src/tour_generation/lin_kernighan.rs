@@ -0,0 +1,277 @@
+use super::brightness::BrightnessCost;
+use super::candidates::CandidateLists;
+use super::local_search::{IMPROVEMENT_EPSILON, run_dont_look_bits};
+use super::metric::Metric;
+use super::or_opt::improve_or_opt;
+use super::path::{EdgeCost, PathEnds};
+use super::tour_array::TourArray;
+use super::turn::turn_delta;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of alternatives tried for the new edge at each depth of the search before
+/// giving up. Deeper levels only follow the most promising alternative.
+const BREADTH: [usize; 2] = [5, 3];
+
+/// Lin–Kernighan style variable-depth improver.
+///
+/// Starting from an edge (t1, t2), each step adds an edge (t2, t3) to a candidate
+/// neighbor and removes (t3, t4), closing the tour with (t4, t1). Chaining these
+/// 2-opt steps yields sequential 3-opt moves and deeper ones, as long as the partial
+/// gain stays positive. When no chain improves the tour from a city, Or-opt segment
+/// insertion is tried instead.
+pub struct LinKernighan {
+    pub metric: Metric,
+    pub neighbors: usize,
+    pub max_depth: usize,
+    pub time_budget: Option<Duration>,
+    /// Maximum number of cities taken from the don't-look bit queue.
+    pub max_iterations: Option<usize>,
+    /// Optimize an open path with these ends instead of a closed tour.
+    pub path: Option<PathEnds>,
+    /// Penalty added to the metric for edges that cross bright parts of the image.
+    pub brightness: Option<Arc<BrightnessCost>>,
+    /// Penalty per radian of turning at each city.
+    pub turn_penalty: f32,
+}
+
+impl Default for LinKernighan {
+    fn default() -> Self {
+        Self {
+            metric: Metric::default(),
+            neighbors: 8,
+            max_depth: 12,
+            time_budget: None,
+            max_iterations: None,
+            path: None,
+            brightness: None,
+            turn_penalty: 0.0,
+        }
+    }
+}
+
+impl LinKernighan {
+    pub fn optimize(&self, points: &[(f32, f32)], tour: &mut [usize]) {
+        if tour.len() < 5 {
+            return;
+        }
+        let cost = EdgeCost::new(
+            points,
+            self.metric,
+            self.path,
+            self.brightness.as_deref(),
+            self.turn_penalty,
+        );
+        let mut nodes: Vec<usize> = cost.to_nodes(tour);
+        let candidates = CandidateLists::from_nodes(&cost, &nodes, self.neighbors);
+        self.optimize_with(&cost, &candidates, &mut nodes);
+        tour.copy_from_slice(&cost.from_nodes(&nodes));
+    }
+
+    /// Same as [`LinKernighan::optimize`] but on a node tour of `cost`, reusing already
+    /// built candidate lists. The metric, path and penalties are taken from `cost`.
+    pub fn optimize_with(&self, cost: &EdgeCost, candidates: &CandidateLists, nodes: &mut [usize]) {
+        if nodes.len() < 5 {
+            return;
+        }
+        let deadline: Option<Instant> = self.time_budget.map(|budget| Instant::now() + budget);
+        let mut search = ChainSearch {
+            cost,
+            candidates,
+            max_depth: self.max_depth.max(1),
+            tour: TourArray::new(nodes, cost.num_nodes()),
+            added: Vec::new(),
+            touched: Vec::new(),
+        };
+
+        run_dont_look_bits(
+            nodes,
+            cost.num_nodes(),
+            deadline,
+            self.max_iterations,
+            |t1| search.improve_city(t1),
+        );
+        nodes.copy_from_slice(search.tour.order());
+    }
+}
+
+struct ChainSearch<'a> {
+    cost: &'a EdgeCost<'a>,
+    candidates: &'a CandidateLists,
+    max_depth: usize,
+    tour: TourArray,
+    /// Edges added by the current chain, which must not be removed again.
+    added: Vec<(usize, usize)>,
+    /// Endpoints of the edges changed by the current chain.
+    touched: Vec<usize>,
+}
+
+impl ChainSearch<'_> {
+    fn dist(&self, a: usize, b: usize) -> f32 {
+        self.cost.distance(a, b)
+    }
+
+    fn improve_city(&mut self, t1: usize) -> Option<Vec<usize>> {
+        for forward in [true, false] {
+            let t2: usize = self.tour.step(t1, forward);
+            self.added.clear();
+            self.touched.clear();
+            self.touched.extend([t1, t2]);
+            if self.step(t1, t2, self.dist(t1, t2), 0.0, 0).is_some() {
+                return Some(self.touched.clone());
+            }
+        }
+        improve_or_opt(self.cost, self.candidates, &mut self.tour, t1)
+    }
+
+    /// Extends the chain that currently closes the tour with the edge (t1, t2) and
+    /// partial gain `gain`, which has changed the turn penalty by `turn`. Returns the
+    /// improvement once an improving tour is reached, keeping it; otherwise every move
+    /// made below this level is undone.
+    fn step(&mut self, t1: usize, t2: usize, gain: f32, turn: f32, depth: usize) -> Option<f32> {
+        let forward: bool = self.tour.next(t1) == t2;
+        let breadth: usize = BREADTH.get(depth).copied().unwrap_or(1);
+
+        let mut alternatives: Vec<(usize, usize, f32)> = Vec::with_capacity(breadth);
+        for &t3 in self.candidates.of(t2) {
+            let g1: f32 = gain - self.dist(t2, t3);
+            // The partial gain only drops along the candidate list when edges cost
+            // their length and nothing else.
+            if g1 <= IMPROVEMENT_EPSILON {
+                if self.cost.is_length_only() {
+                    break;
+                }
+                continue;
+            }
+            if t3 == t1 || t3 == self.tour.next(t2) || t3 == self.tour.prev(t2) {
+                continue;
+            }
+            let t4: usize = self.tour.step(t3, !forward);
+            if t4 == t2 || self.is_added(t3, t4) {
+                continue;
+            }
+            alternatives.push((t3, t4, g1 + self.dist(t3, t4)));
+        }
+        alternatives.sort_by(|a, b| b.2.total_cmp(&a.2));
+        alternatives.truncate(breadth);
+
+        for (t3, t4, new_gain) in alternatives {
+            // t1 t2 .. t4 t3  ->  t1 t4 .. t2 t3
+            let turn: f32 = turn
+                + turn_delta(
+                    self.cost,
+                    &self.tour,
+                    &[(t1, t2), (t3, t4)],
+                    &[(t2, t3), (t4, t1)],
+                );
+            self.tour.two_opt_move(t2, t1, t3, t4);
+            self.added.push((t2, t3));
+            let touched_len: usize = self.touched.len();
+            self.touched.extend([t3, t4]);
+
+            let improvement: f32 = new_gain - self.dist(t4, t1) - turn;
+            if improvement > IMPROVEMENT_EPSILON {
+                return Some(improvement);
+            }
+            if depth + 1 < self.max_depth
+                && let Some(improvement) = self.step(t1, t4, new_gain, turn, depth + 1)
+            {
+                return Some(improvement);
+            }
+
+            self.tour.two_opt_move(t2, t3, t1, t4);
+            self.added.pop();
+            self.touched.truncate(touched_len);
+        }
+        None
+    }
+
+    fn is_added(&self, a: usize, b: usize) -> bool {
+        self.added
+            .iter()
+            .any(|&(x, y)| (x == a && y == b) || (x == b && y == a))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Runs single chains from every city of random tours and checks the gain each
+    /// improving chain reports against the change of the tour cost.
+    fn assert_gains_match(cost: &EdgeCost) {
+        let mut rng: StdRng = StdRng::seed_from_u64(9);
+        let mut cities: Vec<usize> = (0..cost.points().len()).collect();
+        cities.shuffle(&mut rng);
+        let nodes: Vec<usize> = cost.to_nodes(&cities);
+        let candidates = CandidateLists::from_nodes(cost, &nodes, 8);
+        let mut search = ChainSearch {
+            cost,
+            candidates: &candidates,
+            max_depth: 12,
+            tour: TourArray::new(&nodes, cost.num_nodes()),
+            added: Vec::new(),
+            touched: Vec::new(),
+        };
+
+        let mut improved: usize = 0;
+        for t1 in 0..cost.num_nodes() {
+            for forward in [true, false] {
+                let t2: usize = search.tour.step(t1, forward);
+                search.added.clear();
+                search.touched.clear();
+                let before: f32 = cost.tour_length(search.tour.order());
+                let gain: Option<f32> = search.step(t1, t2, cost.distance(t1, t2), 0.0, 0);
+                let after: f32 = cost.tour_length(search.tour.order());
+                match gain {
+                    Some(gain) => {
+                        improved += 1;
+                        assert!(
+                            (before - after - gain).abs() < 1e-5 * before,
+                            "{} {} {}",
+                            before,
+                            after,
+                            gain
+                        );
+                    }
+                    None => assert!((before - after).abs() < 1e-5 * before),
+                }
+            }
+        }
+        assert!(improved > 10);
+    }
+
+    fn random_points(n: usize) -> Vec<(f32, f32)> {
+        let mut rng: StdRng = StdRng::seed_from_u64(4);
+        (0..n)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect()
+    }
+
+    #[test]
+    fn chain_gains_match_the_tour_length_change() {
+        let points: Vec<(f32, f32)> = random_points(200);
+        assert_gains_match(&EdgeCost::new(&points, Metric::Euclidean, None, None, 0.0));
+        assert_gains_match(&EdgeCost::new(&points, Metric::Manhattan, None, None, 0.0));
+    }
+
+    #[test]
+    fn chain_gains_include_turns_and_path_ends() {
+        let points: Vec<(f32, f32)> = random_points(200);
+        let path = PathEnds {
+            start: Some((0.0, 0.0)),
+            end: None,
+        };
+        assert_gains_match(&EdgeCost::new(&points, Metric::Euclidean, None, None, 5.0));
+        assert_gains_match(&EdgeCost::new(
+            &points,
+            Metric::Euclidean,
+            Some(path),
+            None,
+            5.0,
+        ));
+    }
+}
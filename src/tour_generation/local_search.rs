@@ -0,0 +1,239 @@
+use super::brightness::BrightnessCost;
+use super::candidates::CandidateLists;
+use super::metric::Metric;
+use super::or_opt::improve_or_opt;
+use super::path::{EdgeCost, PathEnds};
+use super::tour_array::TourArray;
+use super::turn::turn_delta;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Moves must shorten the tour by more than this to be applied, so that rounding
+/// noise cannot make the search cycle.
+pub const IMPROVEMENT_EPSILON: f32 = 1e-4;
+
+/// How many cities are processed between two checks of the time budget.
+const DEADLINE_CHECK_INTERVAL: usize = 128;
+
+/// Neighbor-list local search with don't-look bits.
+///
+/// Only moves that connect a city to one of its `neighbors` nearest candidates are
+/// evaluated, and a city is only revisited after one of its tour edges changed.
+/// 2-opt and Or-opt moves are interleaved on the same queue of cities. The search
+/// runs until no improving move is left or `time_budget` is spent.
+pub struct LocalSearch {
+    pub metric: Metric,
+    pub neighbors: usize,
+    pub time_budget: Option<Duration>,
+    pub two_opt: bool,
+    pub or_opt: bool,
+    /// Optimize an open path with these ends instead of a closed tour.
+    pub path: Option<PathEnds>,
+    /// Penalty added to the metric for edges that cross bright parts of the image.
+    pub brightness: Option<Arc<BrightnessCost>>,
+    /// Penalty per radian of turning at each city.
+    pub turn_penalty: f32,
+}
+
+impl Default for LocalSearch {
+    fn default() -> Self {
+        Self {
+            metric: Metric::default(),
+            neighbors: 10,
+            time_budget: None,
+            two_opt: true,
+            or_opt: true,
+            path: None,
+            brightness: None,
+            turn_penalty: 0.0,
+        }
+    }
+}
+
+impl LocalSearch {
+    pub fn optimize(&self, points: &[(f32, f32)], tour: &mut [usize]) {
+        if tour.len() < 4 {
+            return;
+        }
+        let cost = EdgeCost::new(
+            points,
+            self.metric,
+            self.path,
+            self.brightness.as_deref(),
+            self.turn_penalty,
+        );
+        let mut nodes: Vec<usize> = cost.to_nodes(tour);
+        let candidates = CandidateLists::from_nodes(&cost, &nodes, self.neighbors);
+        self.optimize_with(&cost, &candidates, &mut nodes);
+        tour.copy_from_slice(&cost.from_nodes(&nodes));
+    }
+
+    /// Same as [`LocalSearch::optimize`] but on a node tour of `cost`, reusing already
+    /// built candidate lists. The metric, path and penalties are taken from `cost`.
+    pub fn optimize_with(&self, cost: &EdgeCost, candidates: &CandidateLists, nodes: &mut [usize]) {
+        if nodes.len() < 4 {
+            return;
+        }
+        let deadline: Option<Instant> = self.time_budget.map(|budget| Instant::now() + budget);
+        let mut array = TourArray::new(nodes, cost.num_nodes());
+        run_dont_look_bits(nodes, cost.num_nodes(), deadline, None, |city| {
+            self.improve_city(cost, candidates, &mut array, city)
+        });
+        nodes.copy_from_slice(array.order());
+    }
+
+    fn improve_city(
+        &self,
+        cost: &EdgeCost,
+        candidates: &CandidateLists,
+        tour: &mut TourArray,
+        city: usize,
+    ) -> Option<Vec<usize>> {
+        if self.two_opt
+            && let Some(touched) = improve_two_opt(cost, candidates, tour, city)
+        {
+            return Some(touched);
+        }
+        if self.or_opt {
+            return improve_or_opt(cost, candidates, tour, city);
+        }
+        None
+    }
+}
+
+/// Processes cities from a queue seeded with `cities` until it runs empty, the
+/// `deadline` passes or `max_iterations` cities were processed. `improve` returns the
+/// cities whose tour edges changed, which get their don't-look bit reset.
+pub fn run_dont_look_bits(
+    cities: &[usize],
+    num_points: usize,
+    deadline: Option<Instant>,
+    max_iterations: Option<usize>,
+    mut improve: impl FnMut(usize) -> Option<Vec<usize>>,
+) {
+    let mut queue: VecDeque<usize> = cities.iter().copied().collect();
+    let mut active: Vec<bool> = vec![false; num_points];
+    for &city in cities {
+        active[city] = true;
+    }
+
+    let mut processed: usize = 0;
+    while let Some(city) = queue.pop_front() {
+        active[city] = false;
+
+        processed += 1;
+        if max_iterations.is_some_and(|max_iterations| processed > max_iterations) {
+            break;
+        }
+        if processed.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            break;
+        }
+
+        if let Some(touched) = improve(city) {
+            for touched_city in touched {
+                if !active[touched_city] {
+                    active[touched_city] = true;
+                    queue.push_back(touched_city);
+                }
+            }
+        }
+    }
+}
+
+/// Tries the 2-opt moves that add an edge between `a` and one of its candidates.
+/// Applies the first improving move and returns the cities whose edges changed.
+fn improve_two_opt(
+    cost: &EdgeCost,
+    candidates: &CandidateLists,
+    tour: &mut TourArray,
+    a: usize,
+) -> Option<Vec<usize>> {
+    let prune: bool = cost.is_length_only();
+    for forward in [true, false] {
+        let b: usize = tour.step(a, forward);
+        let d_ab: f32 = cost.distance(a, b);
+
+        for &c in candidates.of(a) {
+            let d_ac: f32 = cost.distance(a, c);
+            if prune && d_ac >= d_ab {
+                break;
+            }
+            let d: usize = tour.step(c, forward);
+            if c == b || d == a {
+                continue;
+            }
+
+            let delta: f32 = d_ac + cost.distance(b, d) - d_ab - cost.distance(c, d)
+                + turn_delta(cost, tour, &[(a, b), (c, d)], &[(a, c), (b, d)]);
+            if delta < -IMPROVEMENT_EPSILON {
+                tour.two_opt_move(a, b, c, d);
+                return Some(vec![a, b, c, d]);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Whether any 2-opt move that adds an edge between `a` and a candidate improves.
+    fn has_improving_two_opt(
+        cost: &EdgeCost,
+        candidates: &CandidateLists,
+        tour: &TourArray,
+        a: usize,
+    ) -> bool {
+        [true, false].into_iter().any(|forward| {
+            let b: usize = tour.step(a, forward);
+            candidates.of(a).iter().any(|&c| {
+                let d: usize = tour.step(c, forward);
+                c != b
+                    && d != a
+                    && cost.distance(a, c) + cost.distance(b, d)
+                        - cost.distance(a, b)
+                        - cost.distance(c, d)
+                        + turn_delta(cost, tour, &[(a, b), (c, d)], &[(a, c), (b, d)])
+                        < -IMPROVEMENT_EPSILON
+            })
+        })
+    }
+
+    #[test]
+    fn penalties_do_not_cut_the_candidate_scan_short() {
+        let mut rng: StdRng = StdRng::seed_from_u64(2);
+        let points: Vec<(f32, f32)> = (0..300)
+            .map(|_| (rng.random_range(0.0..64.0), rng.random_range(0.0..64.0)))
+            .collect();
+        // Bright stripes make some short edges expensive.
+        let image = GrayImage::from_fn(64, 64, |x, _| Luma([if x % 8 < 4 { 255 } else { 0 }]));
+        let brightness = BrightnessCost::new(&image, 4.0);
+        let costs: [EdgeCost; 2] = [
+            EdgeCost::new(&points, Metric::Euclidean, None, Some(&brightness), 0.0),
+            EdgeCost::new(&points, Metric::Euclidean, None, None, 3.0),
+        ];
+        for cost in &costs {
+            // A tour that is already 2-optimal by length, so that the moves left pay
+            // off through the penalties.
+            let mut cities: Vec<usize> = (0..points.len()).collect();
+            cities.shuffle(&mut rng);
+            LocalSearch::default().optimize(&points, &mut cities);
+            let nodes: Vec<usize> = cost.to_nodes(&cities);
+            let candidates = CandidateLists::from_nodes(cost, &nodes, 10);
+            for a in 0..points.len() {
+                let mut tour = TourArray::new(&nodes, cost.num_nodes());
+                let expected: bool = has_improving_two_opt(cost, &candidates, &tour, a);
+                let found: bool = improve_two_opt(cost, &candidates, &mut tour, a).is_some();
+                assert_eq!(found, expected, "city {}", a);
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use super::candidates::CandidateLists;
+use super::metric::Metric;
+use super::tour_strategy::{HilbertCurveStrategy, TourConfig, TourStrategy};
+use super::utils::find;
+use rayon::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Iterations without a better bound before the step size is halved.
+const PATIENCE: usize = 10;
+
+/// Step size scale below which the subgradient search stops.
+const MIN_STEP_SCALE: f64 = 1e-3;
+
+/// Up to this many points the 1-trees are built over the complete graph.
+const COMPLETE_GRAPH_MAX_POINTS: usize = 500;
+
+/// Held–Karp lower bound on the length of any tour through the points.
+///
+/// A 1-tree is a spanning tree of every point but one, plus the two cheapest edges
+/// from that point, so every tour is a 1-tree and the minimum 1-tree length bounds
+/// the optimal tour from below. Subgradient optimization adds penalties to the points
+/// that push the 1-tree towards degree 2 everywhere, which tightens the bound.
+///
+/// Above `COMPLETE_GRAPH_MAX_POINTS` points, the 1-trees are built over the candidate
+/// graph of nearest neighbors, closed with a Hilbert curve cycle to keep it connected,
+/// rather than the complete graph. With enough neighbors this matches the
+/// complete-graph bound in practice, but it is no longer guaranteed: a minimum 1-tree
+/// that misses edges of the complete graph can be longer than the optimal tour.
+pub struct HeldKarpBound {
+    pub metric: Metric,
+    pub neighbors: usize,
+    pub max_iterations: usize,
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for HeldKarpBound {
+    fn default() -> Self {
+        Self {
+            metric: Metric::default(),
+            neighbors: 10,
+            max_iterations: 200,
+            time_budget: None,
+        }
+    }
+}
+
+impl HeldKarpBound {
+    /// Lower bound on the optimal tour length. `upper_bound` is the length of any
+    /// tour through the points and only scales the subgradient steps.
+    pub fn lower_bound(&self, points: &[(f32, f32)], upper_bound: f32) -> f32 {
+        let n: usize = points.len();
+        if n < 3 {
+            return self
+                .metric
+                .tour_length(points, &(0..n).collect::<Vec<usize>>());
+        }
+        let deadline: Option<Instant> = self.time_budget.map(|budget| Instant::now() + budget);
+        let graph: Vec<(usize, usize, f32)> = self.candidate_graph(points);
+
+        let mut penalties: Vec<f64> = vec![0.0; n];
+        let mut best: f64 = f64::NEG_INFINITY;
+        let mut step_scale: f64 = 2.0;
+        let mut since_improvement: usize = 0;
+        for _ in 0..self.max_iterations {
+            let (length, degrees) = one_tree(n, &graph, &penalties);
+            let bound: f64 = length - 2.0 * penalties.iter().sum::<f64>();
+            if bound > best {
+                best = bound;
+                since_improvement = 0;
+            } else {
+                since_improvement += 1;
+                if since_improvement >= PATIENCE {
+                    step_scale /= 2.0;
+                    since_improvement = 0;
+                }
+            }
+
+            let norm: f64 = degrees
+                .iter()
+                .map(|&degree| (degree as f64 - 2.0).powi(2))
+                .sum();
+            if norm == 0.0 {
+                // The 1-tree is a tour, so it is optimal.
+                break;
+            }
+            if step_scale < MIN_STEP_SCALE || deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            let step: f64 = step_scale * (upper_bound as f64 - bound).max(0.0) / norm;
+            if step == 0.0 {
+                break;
+            }
+            for (penalty, &degree) in penalties.iter_mut().zip(&degrees) {
+                *penalty += step * (degree as f64 - 2.0);
+            }
+        }
+        best as f32
+    }
+
+    /// Undirected edges between candidate neighbors and along a closed Hilbert curve,
+    /// or between all points when there are few of them, with their lengths.
+    fn candidate_graph(&self, points: &[(f32, f32)]) -> Vec<(usize, usize, f32)> {
+        let n: usize = points.len();
+        if n <= COMPLETE_GRAPH_MAX_POINTS {
+            return (0..n)
+                .flat_map(|a| (a + 1..n).map(move |b| (a, b)))
+                .map(|(a, b)| (a, b, self.metric.distance(points[a], points[b])))
+                .collect();
+        }
+        let candidates = CandidateLists::new(points, self.neighbors, self.metric);
+        let hilbert: Vec<usize> = HilbertCurveStrategy
+            .build_tour(points, &TourConfig::default())
+            .map(|tour| tour.into_order())
+            .unwrap_or_default();
+
+        let mut pairs: Vec<(usize, usize)> = (0..n)
+            .flat_map(|a| candidates.of(a).iter().map(move |&b| (a.min(b), a.max(b))))
+            .chain((0..hilbert.len()).map(|i| {
+                let (a, b) = (hilbert[i], hilbert[(i + 1) % hilbert.len()]);
+                (a.min(b), a.max(b))
+            }))
+            .filter(|&(a, b)| a != b)
+            .collect();
+        pairs.par_sort_unstable();
+        pairs.dedup();
+        pairs
+            .into_par_iter()
+            .map(|(a, b)| (a, b, self.metric.distance(points[a], points[b])))
+            .collect()
+    }
+}
+
+/// Length of the minimum 1-tree under `penalties`, with point 0 as the special point,
+/// and the degree of every point in it.
+fn one_tree(n: usize, graph: &[(usize, usize, f32)], penalties: &[f64]) -> (f64, Vec<usize>) {
+    let weight =
+        |&(a, b, length): &(usize, usize, f32)| length as f64 + penalties[a] + penalties[b];
+    let mut edges: Vec<(f64, usize, usize)> = graph
+        .par_iter()
+        .map(|edge| (weight(edge), edge.0, edge.1))
+        .collect();
+    edges.par_sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut degrees: Vec<usize> = vec![0; n];
+    let mut length: f64 = 0.0;
+    let mut special_edges: usize = 0;
+    for (weight, a, b) in edges {
+        if a == 0 {
+            // Edges are sorted, so the first two are the cheapest from point 0.
+            if special_edges < 2 {
+                special_edges += 1;
+            } else {
+                continue;
+            }
+        } else {
+            let root_a: usize = find(&mut parent, a);
+            let root_b: usize = find(&mut parent, b);
+            if root_a == root_b {
+                continue;
+            }
+            parent[root_a] = root_b;
+        }
+        length += weight;
+        degrees[a] += 1;
+        degrees[b] += 1;
+    }
+    (length, degrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bound_stays_below_the_optimal_grid_tour() {
+        // A grid with an even side has a tour of unit steps through every point.
+        let points: Vec<(f32, f32)> = (0..100)
+            .map(|i| ((i % 10) as f32, (i / 10) as f32))
+            .collect();
+        let bound: f32 = HeldKarpBound::default().lower_bound(&points, 120.0);
+        assert!(bound <= 100.0 + 1e-3, "{}", bound);
+        assert!(bound >= 95.0, "{}", bound);
+    }
+
+    #[test]
+    fn coincident_points_have_a_zero_bound() {
+        let points: Vec<(f32, f32)> = vec![(3.0, 4.0); 10];
+        assert_eq!(HeldKarpBound::default().lower_bound(&points, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,96 @@
+/// Cost of travelling between two points, shared by tour construction, local search
+/// and tour length reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Metric {
+    /// Straight line length, i.e. the amount of ink that is plotted.
+    #[default]
+    Euclidean,
+    /// dx² + dy². Penalizes long edges much more than short ones.
+    SquaredEuclidean,
+    /// |dx| + |dy|.
+    Manhattan,
+    /// Travel time of a plotter whose axes move independently at the given speeds.
+    Plotter { x_speed: f32, y_speed: f32 },
+}
+
+impl Metric {
+    #[inline]
+    pub fn distance(&self, p1: (f32, f32), p2: (f32, f32)) -> f32 {
+        let dx: f32 = p2.0 - p1.0;
+        let dy: f32 = p2.1 - p1.1;
+        match *self {
+            Metric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Metric::SquaredEuclidean => dx * dx + dy * dy,
+            Metric::Manhattan => dx.abs() + dy.abs(),
+            Metric::Plotter { x_speed, y_speed } => (dx.abs() / x_speed).max(dy.abs() / y_speed),
+        }
+    }
+
+    /// Length of the closed tour, including the edge back to the first point.
+    pub fn tour_length(&self, points: &[(f32, f32)], tour: &[usize]) -> f32 {
+        let n: usize = tour.len();
+        if n < 2 {
+            return 0.0;
+        }
+        (0..n)
+            .map(|i| self.distance(points[tour[i]], points[tour[(i + 1) % n]]))
+            .sum()
+    }
+
+    /// Length of the open path, without the edge back to the first point.
+    pub fn path_length(&self, points: &[(f32, f32)], path: &[usize]) -> f32 {
+        path.windows(2)
+            .map(|pair| self.distance(points[pair[0]], points[pair[1]]))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances_of_a_3_4_5_triangle() {
+        let (a, b) = ((1.0, 2.0), (4.0, -2.0));
+        assert_eq!(Metric::Euclidean.distance(a, b), 5.0);
+        assert_eq!(Metric::SquaredEuclidean.distance(a, b), 25.0);
+        assert_eq!(Metric::Manhattan.distance(a, b), 7.0);
+        for metric in [
+            Metric::Euclidean,
+            Metric::SquaredEuclidean,
+            Metric::Manhattan,
+        ] {
+            assert_eq!(metric.distance(a, b), metric.distance(b, a));
+            assert_eq!(metric.distance(a, a), 0.0);
+        }
+    }
+
+    #[test]
+    fn plotter_waits_for_the_slower_axis() {
+        let plotter = Metric::Plotter {
+            x_speed: 2.0,
+            y_speed: 0.5,
+        };
+        // 6 along x takes 3, 1 along y takes 2.
+        assert_eq!(plotter.distance((0.0, 0.0), (6.0, 1.0)), 3.0);
+        // 2 along x takes 1, 3 along y takes 6.
+        assert_eq!(plotter.distance((2.0, 3.0), (0.0, 0.0)), 6.0);
+        assert_eq!(plotter.distance((0.0, 0.0), (-6.0, 0.0)), 3.0);
+        // With equal speeds it is the Chebyshev distance.
+        let even = Metric::Plotter {
+            x_speed: 1.0,
+            y_speed: 1.0,
+        };
+        assert_eq!(even.distance((0.0, 0.0), (3.0, -4.0)), 4.0);
+    }
+
+    #[test]
+    fn tour_and_path_lengths() {
+        let points = [(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)];
+        assert_eq!(Metric::Euclidean.tour_length(&points, &[0, 1, 2]), 12.0);
+        assert_eq!(Metric::Euclidean.path_length(&points, &[0, 1, 2]), 7.0);
+        assert_eq!(Metric::Manhattan.tour_length(&points, &[0, 1, 2]), 14.0);
+        assert_eq!(Metric::Euclidean.tour_length(&points, &[1]), 0.0);
+        assert_eq!(Metric::Euclidean.path_length(&points, &[]), 0.0);
+    }
+}
@@ -1,4 +1,32 @@
+pub mod auto;
+pub mod brightness;
+pub mod candidates;
 pub mod cheapest_insertion;
+pub mod farthest_insertion;
 pub mod greedy;
+pub mod greedy_edge;
+pub mod hull;
+pub mod insertion;
+pub mod intersections;
+pub mod lin_kernighan;
+pub mod local_search;
+pub mod lower_bound;
+pub mod metric;
+pub mod multi_tour;
+pub mod nearest_neighbor;
+pub mod optimizer;
+pub mod or_opt;
+pub mod partition;
+pub mod path;
+pub mod random_insertion;
+pub mod registry;
+pub mod simulated_annealing;
+pub mod space_filling_curve;
+pub mod stats;
+pub mod strokes;
+pub mod tour_array;
 pub mod tour_strategy;
+pub mod turn;
+pub mod uncross;
 pub mod utils;
+pub mod warm_start;
@@ -0,0 +1,348 @@
+use super::metric::Metric;
+use super::partition::solve_subset;
+use super::stats::validate_tour;
+use super::tour_strategy::{Tour, TourConfig, TourError, TourStrategy};
+use crate::stippling::point::PointColor;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+/// Position and color of a point, each scaled to about 0..1.
+type Feature = [f32; 5];
+
+/// What the points are grouped by before every group gets its own tour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClusterBy {
+    /// Compact regions of the image.
+    #[default]
+    Position,
+    /// Similar stipple colors, wherever they are.
+    Color,
+    /// Similar colors that are also close together.
+    PositionAndColor,
+}
+
+/// Several closed tours that together visit every point exactly once, e.g. one per
+/// pen of a plotter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultiTour {
+    tours: Vec<Tour>,
+}
+
+impl MultiTour {
+    pub fn new(tours: Vec<Tour>) -> Self {
+        Self { tours }
+    }
+
+    pub fn tours(&self) -> &[Tour] {
+        &self.tours
+    }
+
+    pub fn tours_mut(&mut self) -> &mut [Tour] {
+        &mut self.tours
+    }
+
+    pub fn into_tours(self) -> Vec<Tour> {
+        self.tours
+    }
+
+    pub fn len(&self) -> usize {
+        self.tours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tours.is_empty()
+    }
+
+    /// Total length of the closed tours.
+    pub fn length(&self, points: &[(f32, f32)], metric: Metric) -> f32 {
+        self.tours
+            .iter()
+            .map(|tour| tour.length(points, metric))
+            .sum()
+    }
+}
+
+/// Splits points into `k` groups with k-means, seeded with k-means++.
+#[derive(Clone, Debug)]
+pub struct Clustering {
+    pub k: usize,
+    pub by: ClusterBy,
+    pub max_iterations: usize,
+}
+
+impl Default for Clustering {
+    fn default() -> Self {
+        Self {
+            k: 2,
+            by: ClusterBy::default(),
+            max_iterations: 50,
+        }
+    }
+}
+
+impl Clustering {
+    /// Point indices of every non-empty cluster. Only `by` decides whether `colors`
+    /// are used.
+    pub fn clusters(
+        &self,
+        points: &[(f32, f32)],
+        colors: &[PointColor],
+        seed: u64,
+    ) -> Vec<Vec<usize>> {
+        let n: usize = points.len();
+        let k: usize = self.k.clamp(1, n.max(1));
+        if n == 0 || k == 1 {
+            return vec![(0..n).collect()];
+        }
+        let features: Vec<Feature> = self.features(points, colors);
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        let mut centers: Vec<Feature> = initial_centers(&features, k, &mut rng);
+        let mut assignment: Vec<usize> = vec![usize::MAX; n];
+
+        for _ in 0..self.max_iterations {
+            let next: Vec<usize> = features
+                .par_iter()
+                .map(|feature| nearest_center(&centers, feature))
+                .collect();
+            if next == assignment {
+                break;
+            }
+            assignment = next;
+
+            let mut sums: Vec<Feature> = vec![[0.0; 5]; k];
+            let mut counts: Vec<usize> = vec![0; k];
+            for (feature, &cluster) in features.iter().zip(&assignment) {
+                counts[cluster] += 1;
+                for (sum, value) in sums[cluster].iter_mut().zip(feature) {
+                    *sum += value;
+                }
+            }
+            for cluster in 0..k {
+                if counts[cluster] > 0 {
+                    centers[cluster] = sums[cluster].map(|sum| sum / counts[cluster] as f32);
+                } else {
+                    // Restart an empty cluster at the point worst served by its center.
+                    let error =
+                        |idx: usize| squared_distance(&features[idx], &centers[assignment[idx]]);
+                    let farthest: usize = (0..n)
+                        .max_by(|&a, &b| error(a).total_cmp(&error(b)))
+                        .unwrap_or(0);
+                    centers[cluster] = features[farthest];
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<usize>> = vec![Vec::new(); k];
+        for (idx, &cluster) in assignment.iter().enumerate() {
+            clusters[cluster].push(idx);
+        }
+        clusters.retain(|cluster| !cluster.is_empty());
+        clusters
+    }
+
+    fn features(&self, points: &[(f32, f32)], colors: &[PointColor]) -> Vec<Feature> {
+        let (mut min, mut max) = (
+            (f32::INFINITY, f32::INFINITY),
+            (f32::NEG_INFINITY, f32::NEG_INFINITY),
+        );
+        for &(x, y) in points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let extent: f32 = (max.0 - min.0).max(max.1 - min.1).max(f32::EPSILON);
+        let (use_position, use_color) = match self.by {
+            ClusterBy::Position => (true, false),
+            ClusterBy::Color => (false, true),
+            ClusterBy::PositionAndColor => (true, true),
+        };
+
+        points
+            .iter()
+            .enumerate()
+            .map(|(idx, &(x, y))| {
+                let mut feature: Feature = [0.0; 5];
+                if use_position {
+                    feature[0] = (x - min.0) / extent;
+                    feature[1] = (y - min.1) / extent;
+                }
+                if use_color && let Some(color) = colors.get(idx) {
+                    feature[2] = color.r as f32 / 255.0;
+                    feature[3] = color.g as f32 / 255.0;
+                    feature[4] = color.b as f32 / 255.0;
+                }
+                feature
+            })
+            .collect()
+    }
+}
+
+/// Builds one closed tour per cluster with `strategy`, in parallel, and checks that
+/// together they visit every point exactly once.
+pub fn build_multi_tour(
+    strategy: &dyn TourStrategy,
+    points: &[(f32, f32)],
+    clusters: &[Vec<usize>],
+    config: &TourConfig,
+) -> Result<MultiTour, TourError> {
+    if points.is_empty() {
+        return Err(TourError::EmptyInput);
+    }
+    let cluster_config = TourConfig {
+        path: None,
+        ..config.clone()
+    };
+    let tours: Vec<Vec<usize>> = clusters
+        .par_iter()
+        .map(|cluster| solve_subset(strategy, points, cluster, &cluster_config))
+        .collect::<Result<Vec<Vec<usize>>, TourError>>()?;
+
+    let visited: Vec<usize> = tours.iter().flatten().copied().collect();
+    validate_tour(points.len(), &visited)?;
+    Ok(MultiTour::new(tours.into_iter().map(Tour::new).collect()))
+}
+
+/// k-means++: every next center is a point drawn with probability proportional to
+/// its squared distance to the closest center so far.
+fn initial_centers(features: &[Feature], k: usize, rng: &mut StdRng) -> Vec<Feature> {
+    let mut centers: Vec<Feature> = vec![features[rng.random_range(0..features.len())]];
+    let mut closest: Vec<f32> = features
+        .iter()
+        .map(|feature| squared_distance(feature, &centers[0]))
+        .collect();
+    while centers.len() < k {
+        let total: f32 = closest.iter().sum();
+        let next: usize = if total > 0.0 {
+            let mut target: f32 = rng.random_range(0.0..total);
+            closest
+                .iter()
+                .position(|&distance| {
+                    target -= distance;
+                    target < 0.0
+                })
+                .unwrap_or(features.len() - 1)
+        } else {
+            rng.random_range(0..features.len())
+        };
+        centers.push(features[next]);
+        for (distance, feature) in closest.iter_mut().zip(features) {
+            *distance = distance.min(squared_distance(feature, &features[next]));
+        }
+    }
+    centers
+}
+
+/// Index of the center closest to `feature`.
+fn nearest_center(centers: &[Feature], feature: &Feature) -> usize {
+    centers
+        .iter()
+        .map(|center| squared_distance(feature, center))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(cluster, _)| cluster)
+}
+
+fn squared_distance(a: &Feature, b: &Feature) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour_generation::tour_strategy::GreedyEdgeStrategy;
+
+    fn points_and_colors(n: usize, seed: u64) -> (Vec<(f32, f32)>, Vec<PointColor>) {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| {
+                let point = (rng.random_range(0.0..200.0), rng.random_range(0.0..100.0));
+                let color = PointColor {
+                    r: rng.random(),
+                    g: rng.random(),
+                    b: rng.random(),
+                };
+                (point, color)
+            })
+            .unzip()
+    }
+
+    fn assert_partition(num_points: usize, groups: &[Vec<usize>]) {
+        let visited: Vec<usize> = groups.iter().flatten().copied().collect();
+        assert_eq!(validate_tour(num_points, &visited), Ok(()));
+        assert!(groups.iter().all(|group| !group.is_empty()));
+    }
+
+    #[test]
+    fn clusters_split_every_point_into_one_group() {
+        let (points, colors) = points_and_colors(500, 1);
+        for by in [
+            ClusterBy::Position,
+            ClusterBy::Color,
+            ClusterBy::PositionAndColor,
+        ] {
+            let clustering = Clustering {
+                k: 5,
+                by,
+                ..Clustering::default()
+            };
+            let clusters: Vec<Vec<usize>> = clustering.clusters(&points, &colors, 3);
+            assert_eq!(clusters.len(), 5, "{:?}", by);
+            assert_partition(points.len(), &clusters);
+            assert_eq!(clustering.clusters(&points, &colors, 3), clusters);
+        }
+    }
+
+    #[test]
+    fn empty_clusters_are_dropped() {
+        // Three positions cannot fill six clusters.
+        let points: Vec<(f32, f32)> = (0..30)
+            .map(|i| [(0.0, 0.0), (50.0, 0.0), (0.0, 50.0)][i % 3])
+            .collect();
+        let clusters: Vec<Vec<usize>> = Clustering {
+            k: 6,
+            ..Clustering::default()
+        }
+        .clusters(&points, &[], 7);
+        assert!(clusters.len() <= 3, "{:?}", clusters);
+        assert_partition(points.len(), &clusters);
+
+        // Without colors every point looks the same to color clustering.
+        let (points, _) = points_and_colors(50, 2);
+        let clusters: Vec<Vec<usize>> = Clustering {
+            k: 4,
+            by: ClusterBy::Color,
+            ..Clustering::default()
+        }
+        .clusters(&points, &[], 7);
+        assert_eq!(clusters, vec![(0..50).collect::<Vec<usize>>()]);
+    }
+
+    #[test]
+    fn multi_tour_visits_every_point_in_one_tour() {
+        let (points, colors) = points_and_colors(600, 4);
+        let clusters: Vec<Vec<usize>> = Clustering {
+            k: 4,
+            ..Clustering::default()
+        }
+        .clusters(&points, &colors, 5);
+        let config = TourConfig::default();
+        let multi_tour: MultiTour =
+            build_multi_tour(&GreedyEdgeStrategy, &points, &clusters, &config).unwrap();
+        assert_eq!(multi_tour.len(), clusters.len());
+        for (tour, cluster) in multi_tour.tours().iter().zip(&clusters) {
+            let mut visited: Vec<usize> = tour.order().to_vec();
+            visited.sort_unstable();
+            assert_eq!(&visited, cluster);
+        }
+        assert_eq!(
+            build_multi_tour(&GreedyEdgeStrategy, &points, &clusters, &config).unwrap(),
+            multi_tour
+        );
+
+        let missing: Vec<Vec<usize>> = vec![(1..points.len()).collect()];
+        assert_eq!(
+            build_multi_tour(&GreedyEdgeStrategy, &points, &missing, &config),
+            Err(TourError::MissingIndex(0))
+        );
+    }
+}
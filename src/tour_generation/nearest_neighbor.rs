@@ -0,0 +1,32 @@
+use super::tour_strategy::{NearestNeighborStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{on_distinct_positions, two_opt_or_opt};
+use kiddo::{KdTree, SquaredEuclidean};
+
+impl TourStrategy for NearestNeighborStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+
+            let mut kdtree = KdTree::<f32, 2>::with_capacity(points.len());
+            for (idx, &(x, y)) in points.iter().enumerate().skip(1) {
+                kdtree.add(&[x, y], idx as u64);
+            }
+
+            let mut tour: Vec<usize> = Vec::with_capacity(points.len());
+            let mut current: usize = 0;
+            tour.push(current);
+            while tour.len() < points.len() {
+                let (x, y) = points[current];
+                let nearest = kdtree.nearest_one::<SquaredEuclidean>(&[x, y]);
+                current = nearest.item as usize;
+                kdtree.remove(&[points[current].0, points[current].1], nearest.item);
+                tour.push(current);
+            }
+
+            two_opt_or_opt(points, &mut tour, config);
+            Ok(Tour::new(tour))
+        })
+    }
+}
@@ -0,0 +1,65 @@
+use super::lin_kernighan::LinKernighan;
+use super::local_search::LocalSearch;
+use super::tour_strategy::TourConfig;
+
+/// Improvement pass that can be chained after any tour construction strategy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TourOptimizer {
+    TwoOpt,
+    OrOpt,
+    LinKernighan,
+}
+
+impl TourOptimizer {
+    /// Improves `tour`, which must be an open path when `config` asks for one.
+    pub fn optimize(&self, points: &[(f32, f32)], tour: &mut [usize], config: &TourConfig) {
+        let metric = config.metric;
+        let time_budget = config.time_budget;
+        let path = config.path;
+        let brightness = config.brightness.clone();
+        let turn_penalty = config.turn_penalty;
+        match self {
+            TourOptimizer::TwoOpt => LocalSearch {
+                metric,
+                time_budget,
+                path,
+                brightness,
+                turn_penalty,
+                or_opt: false,
+                ..LocalSearch::default()
+            }
+            .optimize(points, tour),
+            TourOptimizer::OrOpt => LocalSearch {
+                metric,
+                time_budget,
+                path,
+                brightness,
+                turn_penalty,
+                two_opt: false,
+                ..LocalSearch::default()
+            }
+            .optimize(points, tour),
+            TourOptimizer::LinKernighan => LinKernighan {
+                metric,
+                time_budget,
+                path,
+                brightness,
+                turn_penalty,
+                ..LinKernighan::default()
+            }
+            .optimize(points, tour),
+        }
+    }
+}
+
+/// Runs `optimizers` one after the other on `tour`.
+pub fn optimize_chain(
+    points: &[(f32, f32)],
+    tour: &mut [usize],
+    config: &TourConfig,
+    optimizers: &[TourOptimizer],
+) {
+    for optimizer in optimizers {
+        optimizer.optimize(points, tour, config);
+    }
+}
@@ -0,0 +1,174 @@
+use super::candidates::CandidateLists;
+use super::local_search::IMPROVEMENT_EPSILON;
+use super::path::EdgeCost;
+use super::tour_array::TourArray;
+use super::turn::turn_delta;
+
+/// Longest run of consecutive points moved by a single Or-opt move.
+pub const MAX_SEGMENT_LEN: usize = 3;
+
+/// Tries to move a run of 1..=`MAX_SEGMENT_LEN` points starting at `s1` between two
+/// other adjacent points, keeping or reversing its orientation. The target edge must
+/// touch a candidate neighbor of one of the segment ends. Applies the first improving
+/// move and returns the cities whose edges changed.
+pub fn improve_or_opt(
+    cost: &EdgeCost,
+    candidates: &CandidateLists,
+    tour: &mut TourArray,
+    s1: usize,
+) -> Option<Vec<usize>> {
+    let dist = |a: usize, b: usize| cost.distance(a, b);
+    // Penalties can make a move improve even where the lengths alone do not.
+    let prune: bool = cost.is_length_only();
+
+    for forward in [true, false] {
+        let p: usize = tour.step(s1, !forward);
+        let mut segment: Vec<usize> = vec![s1];
+
+        for _ in 0..MAX_SEGMENT_LEN {
+            if tour.len() < segment.len() + 3 {
+                break;
+            }
+            let s2: usize = *segment.last().unwrap();
+            let nx: usize = tour.step(s2, forward);
+            let removal_gain: f32 = dist(p, s1) + dist(s2, nx) - dist(p, nx);
+
+            if !prune || removal_gain > IMPROVEMENT_EPSILON {
+                for (end, other) in [(s1, s2), (s2, s1)] {
+                    for &c in candidates.of(end) {
+                        let d_ce: f32 = dist(c, end);
+                        if prune && d_ce >= removal_gain {
+                            break;
+                        }
+                        if segment.contains(&c) {
+                            continue;
+                        }
+                        for d in [tour.next(c), tour.prev(c)] {
+                            if segment.contains(&d) {
+                                continue;
+                            }
+                            let delta: f32 = d_ce + dist(other, d) - dist(c, d) - removal_gain
+                                + turn_delta(
+                                    cost,
+                                    tour,
+                                    &[(p, s1), (s2, nx), (c, d)],
+                                    &[(p, nx), (c, end), (other, d)],
+                                );
+                            if delta < -IMPROVEMENT_EPSILON {
+                                move_segment(tour, forward, p, s1, s2, nx, c, d, end);
+                                return Some(vec![p, s1, s2, nx, c, d]);
+                            }
+                        }
+                    }
+                }
+            }
+
+            segment.push(nx);
+        }
+    }
+    None
+}
+
+/// Moves the segment s1..s2 (walked in direction `forward`, with neighbors `p` and
+/// `nx`) between the adjacent cities `c` and `d`, so that `c` ends up next to `end`.
+/// The move is applied as a sequence of 2-opt moves.
+#[allow(clippy::too_many_arguments)]
+pub fn move_segment(
+    tour: &mut TourArray,
+    forward: bool,
+    p: usize,
+    s1: usize,
+    s2: usize,
+    nx: usize,
+    c: usize,
+    d: usize,
+    end: usize,
+) {
+    // Orient the target edge like (p, s1), flipping which side of it `end` joins.
+    let (c, d, c_joins_s1) = if tour.step(c, forward) == d {
+        (c, d, end == s1)
+    } else {
+        (d, c, end != s1)
+    };
+
+    // p s1..s2 nx .. c d  ->  p c .. nx s2..s1 d
+    tour.two_opt_move(p, s1, c, d);
+    // p c .. nx s2..s1 d  ->  p nx .. c s2..s1 d
+    tour.two_opt_move(p, c, nx, s2);
+    if c_joins_s1 {
+        // c s2..s1 d  ->  c s1..s2 d
+        tour.two_opt_move(c, s2, s1, d);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Unordered neighbors of every city of a closed tour.
+    fn neighbors(order: &[usize]) -> Vec<[usize; 2]> {
+        let n: usize = order.len();
+        let mut neighbors: Vec<[usize; 2]> = vec![[0; 2]; n];
+        for i in 0..n {
+            let (a, b) = (order[(i + n - 1) % n], order[(i + 1) % n]);
+            neighbors[order[i]] = [a.min(b), a.max(b)];
+        }
+        neighbors
+    }
+
+    #[test]
+    fn move_segment_reinserts_the_segment_between_the_target_cities() {
+        let mut rng: StdRng = StdRng::seed_from_u64(11);
+        for _ in 0..2000 {
+            let n: usize = rng.random_range(8..20);
+            let mut cities: Vec<usize> = (0..n).collect();
+            cities.shuffle(&mut rng);
+            let mut tour = TourArray::new(&cities, n);
+
+            let forward: bool = rng.random();
+            let s1: usize = rng.random_range(0..n);
+            let mut segment: Vec<usize> = vec![s1];
+            for _ in 1..rng.random_range(1..=MAX_SEGMENT_LEN) {
+                segment.push(tour.step(*segment.last().unwrap(), forward));
+            }
+            let s2: usize = *segment.last().unwrap();
+            let (p, nx) = (tour.step(s1, !forward), tour.step(s2, forward));
+            let c: usize = loop {
+                let c: usize = rng.random_range(0..n);
+                if !segment.contains(&c) && c != nx && c != p {
+                    break c;
+                }
+            };
+            let d: usize = tour.step(c, rng.random());
+            if segment.contains(&d) {
+                continue;
+            }
+            let end: usize = if rng.random() { s1 } else { s2 };
+
+            // The rest of the tour from nx to p in the walking direction, with the
+            // segment put back between c and d, `end` next to c.
+            let mut rest: Vec<usize> = vec![nx];
+            while *rest.last().unwrap() != p {
+                rest.push(tour.step(*rest.last().unwrap(), forward));
+            }
+            let (i, j) = (
+                rest.iter().position(|&city| city == c).unwrap(),
+                rest.iter().position(|&city| city == d).unwrap(),
+            );
+            let mut inserted: Vec<usize> = segment.clone();
+            if (i < j) != (inserted[0] == end) {
+                inserted.reverse();
+            }
+            rest.splice(i.max(j)..i.max(j), inserted);
+
+            move_segment(&mut tour, forward, p, s1, s2, nx, c, d, end);
+            assert_eq!(neighbors(tour.order()), neighbors(&rest));
+            for (k, &city) in tour.order().iter().enumerate() {
+                assert_eq!(tour.position(city), k);
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use super::path::EdgeCost;
+use super::tour_strategy::{
+    CheapestInsertionStrategy, PartitionStrategy, Tour, TourConfig, TourError, TourStrategy,
+};
+use super::utils::{on_distinct_positions, remaining, two_opt_or_opt};
+use kiddo::{KdTree, SquaredEuclidean};
+use rayon::prelude::*;
+use std::time::Instant;
+
+/// Number of already merged points near each point of a new sub-tour whose edges are
+/// tried when merging it.
+const MERGE_NEIGHBORS: usize = 6;
+
+impl Default for PartitionStrategy {
+    fn default() -> Self {
+        Self {
+            cell_strategy: Box::new(CheapestInsertionStrategy),
+            cell_size: 5000,
+        }
+    }
+}
+
+impl TourStrategy for PartitionStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+
+            let mut cells: Vec<Vec<usize>> = Vec::new();
+            split(
+                points,
+                (0..points.len()).collect(),
+                self.cell_size.max(1),
+                &mut cells,
+            );
+
+            // The cells share the first half of the time budget, and the local search
+            // over the stitched tour gets the rest.
+            let start: Instant = Instant::now();
+            let deadline: Option<Instant> = config.time_budget.map(|budget| start + budget);
+            let cells_deadline: Option<Instant> =
+                config.time_budget.map(|budget| start + budget / 2);
+            let sub_tours: Vec<Vec<usize>> = cells
+                .par_iter()
+                .map(|cell| {
+                    // Cells are always closed, the stitched tour is cut at the end if
+                    // needed.
+                    let cell_config = TourConfig {
+                        path: None,
+                        time_budget: remaining(cells_deadline),
+                        ..config.clone()
+                    };
+                    solve_subset(self.cell_strategy.as_ref(), points, cell, &cell_config)
+                })
+                .collect::<Result<Vec<Vec<usize>>, TourError>>()?;
+
+            let mut tour: Vec<usize> = stitch(&EdgeCost::from_config(points, config), &sub_tours);
+            let stitched_config = TourConfig {
+                time_budget: remaining(deadline),
+                ..config.clone()
+            };
+            two_opt_or_opt(points, &mut tour, &stitched_config);
+            Ok(Tour::new(tour))
+        })
+    }
+}
+
+/// Tour of the points `subset` built by `strategy`, as global point indices.
+pub fn solve_subset(
+    strategy: &dyn TourStrategy,
+    points: &[(f32, f32)],
+    subset: &[usize],
+    config: &TourConfig,
+) -> Result<Vec<usize>, TourError> {
+    if subset.len() <= 3 {
+        return Ok(subset.to_vec());
+    }
+    let subset_points: Vec<(f32, f32)> = subset.iter().map(|&idx| points[idx]).collect();
+    let tour: Tour = strategy.build_tour(&subset_points, config)?;
+    Ok(tour.order().iter().map(|&local| subset[local]).collect())
+}
+
+/// Recursively splits `indices` at the median of the wider side of their bounding box
+/// until every cell has at most `cell_size` points. Cells are emitted in traversal
+/// order, so consecutive cells are usually neighbors.
+fn split(
+    points: &[(f32, f32)],
+    mut indices: Vec<usize>,
+    cell_size: usize,
+    cells: &mut Vec<Vec<usize>>,
+) {
+    if indices.len() <= cell_size {
+        cells.push(indices);
+        return;
+    }
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for &idx in &indices {
+        let (x, y) = points[idx];
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    let coordinate = |idx: &usize| -> f32 {
+        if max_x - min_x >= max_y - min_y {
+            points[*idx].0
+        } else {
+            points[*idx].1
+        }
+    };
+
+    let mid: usize = indices.len() / 2;
+    indices.select_nth_unstable_by(mid, |a, b| coordinate(a).total_cmp(&coordinate(b)));
+    let upper: Vec<usize> = indices.split_off(mid);
+    split(points, indices, cell_size, cells);
+    split(points, upper, cell_size, cells);
+}
+
+/// Merges the sub-tours one at a time into a single closed tour. Each merge removes
+/// one edge from the merged tour and one from the sub-tour and reconnects their ends,
+/// picking the cheapest pair among edges of nearby points.
+fn stitch(cost: &EdgeCost, sub_tours: &[Vec<usize>]) -> Vec<usize> {
+    let points: &[(f32, f32)] = cost.points();
+    let dist = |a: usize, b: usize| cost.distance(a, b);
+    let mut next: Vec<usize> = vec![usize::MAX; points.len()];
+    let mut prev: Vec<usize> = vec![usize::MAX; points.len()];
+    let mut merged = KdTree::<f32, 2>::with_capacity(points.len());
+    let mut start: Option<usize> = None;
+
+    for sub_tour in sub_tours.iter().filter(|sub_tour| !sub_tour.is_empty()) {
+        for (i, &b) in sub_tour.iter().enumerate() {
+            let b_next: usize = sub_tour[(i + 1) % sub_tour.len()];
+            next[b] = b_next;
+            prev[b_next] = b;
+        }
+
+        if start.is_some() {
+            // (cost, a1, b1, reversed): remove (a1, next[a1]) and (b1, next[b1]).
+            let mut best: (f32, usize, usize, bool) = (f32::INFINITY, 0, 0, false);
+            for &b1 in sub_tour {
+                let b2: usize = next[b1];
+                let (x, y) = points[b1];
+                for neighbor in merged.nearest_n::<SquaredEuclidean>(&[x, y], MERGE_NEIGHBORS) {
+                    let a: usize = neighbor.item as usize;
+                    for a1 in [prev[a], a] {
+                        let a2: usize = next[a1];
+                        let removed: f32 = dist(a1, a2) + dist(b1, b2);
+                        // a1 -> b2 .. b1 -> a2
+                        let kept: f32 = dist(a1, b2) + dist(b1, a2) - removed;
+                        if kept < best.0 {
+                            best = (kept, a1, b1, false);
+                        }
+                        // a1 -> b1 .. b2 -> a2, walking the sub-tour backwards
+                        let reversed: f32 = dist(a1, b1) + dist(b2, a2) - removed;
+                        if reversed < best.0 {
+                            best = (reversed, a1, b1, true);
+                        }
+                    }
+                }
+            }
+
+            let (_, a1, b1, reversed) = best;
+            let a2: usize = next[a1];
+            let b2: usize = next[b1];
+            let (first, last) = if reversed {
+                for &b in sub_tour {
+                    std::mem::swap(&mut next[b], &mut prev[b]);
+                }
+                (b1, b2)
+            } else {
+                (b2, b1)
+            };
+            next[a1] = first;
+            prev[first] = a1;
+            next[last] = a2;
+            prev[a2] = last;
+        } else {
+            start = Some(sub_tour[0]);
+        }
+
+        for &b in sub_tour {
+            merged.add(&[points[b].0, points[b].1], b as u64);
+        }
+    }
+
+    let start: usize = start.unwrap_or_default();
+    let mut tour: Vec<usize> = Vec::with_capacity(points.len());
+    let mut current: usize = start;
+    loop {
+        tour.push(current);
+        current = next[current];
+        if current == start {
+            break;
+        }
+    }
+    tour
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour_generation::metric::Metric;
+    use crate::tour_generation::stats::validate_tour;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_points(n: usize, seed: u64) -> Vec<(f32, f32)> {
+        let mut rng: StdRng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect()
+    }
+
+    #[test]
+    fn stitch_joins_cells_into_one_tour() {
+        let points: Vec<(f32, f32)> = random_points(500, 1);
+        let cost = EdgeCost::new(&points, Metric::Euclidean, None, None, 0.0);
+        // Cells down to single points, whose sub-tours are left as they are.
+        for cell_size in [1, 2, 3, 7, 64] {
+            let mut cells: Vec<Vec<usize>> = Vec::new();
+            split(&points, (0..points.len()).collect(), cell_size, &mut cells);
+            assert!(cells.iter().all(|cell| cell.len() <= cell_size));
+            let tour: Vec<usize> = stitch(&cost, &cells);
+            assert_eq!(validate_tour(points.len(), &tour), Ok(()), "{}", cell_size);
+        }
+    }
+
+    #[test]
+    fn partition_builds_a_valid_tour_across_cell_borders() {
+        let points: Vec<(f32, f32)> = random_points(2000, 2);
+        let strategy = PartitionStrategy {
+            cell_size: 100,
+            ..PartitionStrategy::default()
+        };
+        let tour: Tour = strategy
+            .build_tour(&points, &TourConfig::default())
+            .unwrap();
+        assert_eq!(validate_tour(points.len(), tour.order()), Ok(()));
+        // Stitching and the local search keep the tour close to one built in one go.
+        let whole: Tour = CheapestInsertionStrategy
+            .build_tour(&points, &TourConfig::default())
+            .unwrap();
+        assert!(
+            tour.length(&points, Metric::Euclidean)
+                < 1.1 * whole.length(&points, Metric::Euclidean)
+        );
+    }
+}
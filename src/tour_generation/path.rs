@@ -0,0 +1,252 @@
+use super::brightness::BrightnessCost;
+use super::metric::Metric;
+use super::tour_strategy::TourConfig;
+use super::turn::turning_angle;
+
+/// Cost of the virtual edge that joins the two ends of an open path. Removing it
+/// always makes a move worse, so the optimizers never break it.
+const FIXED_EDGE_COST: f32 = -1e9;
+
+/// Ends of an open path. A pinned end is a position the path starts or finishes
+/// near: the distance to it counts towards the length being minimized. Free ends can
+/// be anywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PathEnds {
+    pub start: Option<(f32, f32)>,
+    pub end: Option<(f32, f32)>,
+}
+
+/// Edge costs seen by the tour optimizers.
+///
+/// A closed tour is optimized over the point indices with the metric distance. An
+/// open path is optimized as a closed tour through two extra virtual nodes,
+/// `points.len()` for the start and `points.len() + 1` for the end, joined by an edge
+/// that is never removed. The other edges of a virtual node cost the distance to its
+/// pinned position, or nothing when that end is free.
+///
+/// Edges between points also pay the `brightness` penalty when one is given, and every
+/// point pays `turn_penalty` per radian of turning between its two edges.
+#[derive(Clone, Copy, Debug)]
+pub struct EdgeCost<'a> {
+    points: &'a [(f32, f32)],
+    metric: Metric,
+    path: Option<PathEnds>,
+    brightness: Option<&'a BrightnessCost>,
+    turn_penalty: f32,
+}
+
+impl<'a> EdgeCost<'a> {
+    pub fn new(
+        points: &'a [(f32, f32)],
+        metric: Metric,
+        path: Option<PathEnds>,
+        brightness: Option<&'a BrightnessCost>,
+        turn_penalty: f32,
+    ) -> Self {
+        Self {
+            points,
+            metric,
+            path,
+            brightness,
+            turn_penalty,
+        }
+    }
+
+    /// Edge costs with the metric, path and penalties of `config`.
+    pub fn from_config(points: &'a [(f32, f32)], config: &'a TourConfig) -> Self {
+        Self::new(
+            points,
+            config.metric,
+            config.path,
+            config.brightness.as_deref(),
+            config.turn_penalty,
+        )
+    }
+
+    pub fn points(&self) -> &'a [(f32, f32)] {
+        self.points
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn path(&self) -> Option<PathEnds> {
+        self.path
+    }
+
+    pub fn brightness(&self) -> Option<&'a BrightnessCost> {
+        self.brightness
+    }
+
+    pub fn turn_penalty(&self) -> f32 {
+        self.turn_penalty
+    }
+
+    /// True when edges cost their metric length and nothing else, so that candidate
+    /// lists, which are sorted by that length, are sorted by cost too and no turn can
+    /// make up for a longer edge. The optimizers only stop scanning a candidate list
+    /// early in that case.
+    pub fn is_length_only(&self) -> bool {
+        self.brightness.is_none() && self.turn_penalty == 0.0
+    }
+
+    /// Number of nodes, i.e. the points plus the virtual ends of an open path.
+    pub fn num_nodes(&self) -> usize {
+        match self.path {
+            Some(_) => self.points.len() + 2,
+            None => self.points.len(),
+        }
+    }
+
+    /// True for the virtual ends of an open path.
+    pub fn is_virtual(&self, node: usize) -> bool {
+        node >= self.points.len()
+    }
+
+    /// Pinned position of a virtual node, None for points and free ends.
+    pub fn anchor(&self, node: usize) -> Option<(f32, f32)> {
+        let path: PathEnds = self.path?;
+        if node == self.points.len() {
+            path.start
+        } else if node == self.points.len() + 1 {
+            path.end
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn distance(&self, a: usize, b: usize) -> f32 {
+        let n: usize = self.points.len();
+        match (a < n, b < n) {
+            (true, true) => self.edge(a, b),
+            (false, false) => FIXED_EDGE_COST,
+            (true, false) => self
+                .anchor(b)
+                .map_or(0.0, |anchor| self.metric.distance(anchor, self.points[a])),
+            (false, true) => self
+                .anchor(a)
+                .map_or(0.0, |anchor| self.metric.distance(anchor, self.points[b])),
+        }
+    }
+
+    /// Cost of the edge between the points `a` and `b`.
+    #[inline]
+    fn edge(&self, a: usize, b: usize) -> f32 {
+        let (p1, p2) = (self.points[a], self.points[b]);
+        let length: f32 = self.metric.distance(p1, p2);
+        match self.brightness {
+            Some(brightness) => length + brightness.penalty(p1, p2),
+            None => length,
+        }
+    }
+
+    /// Turn penalty at `v` between its neighbors `u` and `w`. The ends of an open path
+    /// do not turn.
+    #[inline]
+    pub fn turn(&self, u: usize, v: usize, w: usize) -> f32 {
+        if self.turn_penalty == 0.0 || [u, v, w].iter().any(|&node| self.is_virtual(node)) {
+            return 0.0;
+        }
+        self.turn_penalty * turning_angle(self.points[u], self.points[v], self.points[w])
+    }
+
+    /// Length of a closed node tour, leaving out the fixed edge between the virtual
+    /// ends. For an open path this is the path length plus the distances to its
+    /// pinned ends. The brightness and turn penalties are included when set.
+    pub fn tour_length(&self, nodes: &[usize]) -> f32 {
+        let n: usize = nodes.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let fixed: usize = self.points.len();
+        let length: f32 = (0..n)
+            .map(|i| (nodes[i], nodes[(i + 1) % n]))
+            .filter(|&(a, b)| a < fixed || b < fixed)
+            .map(|(a, b)| self.distance(a, b))
+            .sum();
+        if self.turn_penalty == 0.0 || n < 3 {
+            return length;
+        }
+        length
+            + (0..n)
+                .map(|i| self.turn(nodes[(i + n - 1) % n], nodes[i], nodes[(i + 1) % n]))
+                .sum::<f32>()
+    }
+
+    /// Node tour visiting `order`, which is a closed tour or an open path of points.
+    pub fn to_nodes(&self, order: &[usize]) -> Vec<usize> {
+        let mut nodes: Vec<usize> = Vec::with_capacity(self.num_nodes());
+        if self.path.is_some() {
+            nodes.push(self.points.len());
+            nodes.extend_from_slice(order);
+            nodes.push(self.points.len() + 1);
+        } else {
+            nodes.extend_from_slice(order);
+        }
+        nodes
+    }
+
+    /// Inverse of [`EdgeCost::to_nodes`]: the points of a node tour, as an open path
+    /// from the start to the end node when the path is open.
+    pub fn from_nodes(&self, nodes: &[usize]) -> Vec<usize> {
+        if self.path.is_none() {
+            return nodes.to_vec();
+        }
+        let n: usize = self.points.len();
+        let start: usize = nodes.iter().position(|&node| node == n).unwrap_or(0);
+        let forward: bool = nodes[(start + 1) % nodes.len()] != n + 1;
+        (1..nodes.len())
+            .map(|offset| {
+                if forward {
+                    nodes[(start + offset) % nodes.len()]
+                } else {
+                    nodes[(start + nodes.len() - offset) % nodes.len()]
+                }
+            })
+            .filter(|&node| node < n)
+            .collect()
+    }
+
+    /// Turns a closed tour of points into an open path in place by dropping the edge
+    /// whose removal, together with the distances to the pinned ends, costs the
+    /// least. Does nothing for closed tours.
+    pub fn cut(&self, tour: &mut [usize]) {
+        let Some(path) = self.path else {
+            return;
+        };
+        let len: usize = tour.len();
+        if len < 2 {
+            return;
+        }
+        let to = |anchor: Option<(f32, f32)>, idx: usize| {
+            anchor.map_or(0.0, |anchor| self.metric.distance(anchor, self.points[idx]))
+        };
+
+        // (cost, position of the first point, reversed)
+        let mut best: (f32, usize, bool) = (f32::INFINITY, 0, false);
+        for i in 0..len {
+            let (a, b) = (tour[i], tour[(i + 1) % len]);
+            let removed: f32 = self.edge(a, b);
+            // b .. a, walking forward
+            let forward: f32 = to(path.start, b) + to(path.end, a) - removed;
+            if forward < best.0 {
+                best = (forward, (i + 1) % len, false);
+            }
+            // a .. b, walking backward
+            let backward: f32 = to(path.start, a) + to(path.end, b) - removed;
+            if backward < best.0 {
+                best = (backward, i, true);
+            }
+        }
+
+        let (_, first, reversed) = best;
+        if reversed {
+            tour.rotate_left((first + 1) % len);
+            tour.reverse();
+        } else {
+            tour.rotate_left(first);
+        }
+    }
+}
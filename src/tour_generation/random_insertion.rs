@@ -0,0 +1,35 @@
+use super::hull::convex_hull;
+use super::insertion::InsertionTour;
+use super::path::EdgeCost;
+use super::tour_strategy::{RandomInsertionStrategy, Tour, TourConfig, TourError, TourStrategy};
+use super::utils::{on_distinct_positions, two_opt_or_opt};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+impl TourStrategy for RandomInsertionStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            if points.is_empty() {
+                return Err(TourError::EmptyInput);
+            }
+            let initial: Vec<usize> = convex_hull(points);
+            let mut tour = InsertionTour::new(EdgeCost::from_config(points, config), &initial);
+
+            let mut remaining: Vec<usize> = (0..points.len())
+                .filter(|&idx| !tour.contains(idx))
+                .collect();
+            let mut rng: StdRng = StdRng::seed_from_u64(config.seed);
+            remaining.shuffle(&mut rng);
+
+            for p in remaining {
+                let (_, a, b) = tour.best_insertion(p);
+                tour.insert(p, a, b);
+            }
+
+            let mut tour: Vec<usize> = tour.order_from(initial[0]);
+            two_opt_or_opt(points, &mut tour, config);
+            Ok(Tour::new(tour))
+        })
+    }
+}
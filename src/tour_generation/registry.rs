@@ -0,0 +1,107 @@
+use super::stats::validate_tour;
+use super::tour_strategy::{
+    AutoStrategy, CheapestInsertionStrategy, FarthestInsertionStrategy, GreedyEdgeStrategy,
+    GreedyStrategy, HilbertCurveStrategy, NearestNeighborStrategy, PartitionStrategy,
+    RandomInsertionStrategy, SimulatedAnnealingStrategy, Tour, TourConfig, TourError, TourStrategy,
+};
+use std::collections::BTreeMap;
+
+/// Tour strategies looked up by name, so they can be chosen at runtime. Library users
+/// can register their own strategies next to the built-in ones.
+#[derive(Default)]
+pub struct StrategyRegistry {
+    strategies: BTreeMap<String, Box<dyn TourStrategy>>,
+}
+
+impl StrategyRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with every strategy shipped by this crate.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("auto", Box::new(AutoStrategy::default()));
+        registry.register("greedy", Box::new(GreedyStrategy));
+        registry.register("cheapest-insertion", Box::new(CheapestInsertionStrategy));
+        registry.register("farthest-insertion", Box::new(FarthestInsertionStrategy));
+        registry.register("random-insertion", Box::new(RandomInsertionStrategy));
+        registry.register("nearest-neighbor", Box::new(NearestNeighborStrategy));
+        registry.register("greedy-edge", Box::new(GreedyEdgeStrategy));
+        registry.register("hilbert", Box::new(HilbertCurveStrategy));
+        registry.register("partition", Box::new(PartitionStrategy::default()));
+        registry.register("annealing", Box::new(SimulatedAnnealingStrategy::default()));
+        registry
+    }
+
+    /// Registers `strategy` under `name`, replacing any strategy with the same name.
+    pub fn register(&mut self, name: impl Into<String>, strategy: Box<dyn TourStrategy>) {
+        self.strategies.insert(name.into(), strategy);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn TourStrategy, TourError> {
+        self.strategies
+            .get(name)
+            .map(|strategy| strategy.as_ref())
+            .ok_or_else(|| TourError::UnknownStrategy(name.to_string()))
+    }
+
+    /// Removes the strategy registered under `name` and returns it, e.g. to wrap it in
+    /// another strategy.
+    pub fn remove(&mut self, name: &str) -> Result<Box<dyn TourStrategy>, TourError> {
+        self.strategies
+            .remove(name)
+            .ok_or_else(|| TourError::UnknownStrategy(name.to_string()))
+    }
+
+    /// Registered names in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.strategies.keys().map(String::as_str).collect()
+    }
+
+    /// Builds a tour with the strategy registered under `name` and checks that it
+    /// visits every point exactly once.
+    pub fn build_tour(
+        &self,
+        name: &str,
+        points: &[(f32, f32)],
+        config: &TourConfig,
+    ) -> Result<Tour, TourError> {
+        let tour: Tour = self.get(name)?.build_tour(points, config)?;
+        validate_tour(points.len(), tour.order())?;
+        Ok(tour)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategies_can_be_registered_under_runtime_names() {
+        let mut registry = StrategyRegistry::with_defaults();
+        for cell_size in [100, 1000] {
+            registry.register(
+                format!("partition-{}", cell_size),
+                Box::new(PartitionStrategy {
+                    cell_size,
+                    ..PartitionStrategy::default()
+                }),
+            );
+        }
+        assert!(registry.names().contains(&"partition-100"));
+        assert!(registry.names().contains(&"partition-1000"));
+
+        let points: Vec<(f32, f32)> = (0..300)
+            .map(|i| ((i * 37 % 101) as f32, (i * 53 % 97) as f32))
+            .collect();
+        let tour: Tour = registry
+            .build_tour("partition-100", &points, &TourConfig::default())
+            .unwrap();
+        assert_eq!(tour.len(), points.len());
+        assert_eq!(
+            registry.get("partition-10").err(),
+            Some(TourError::UnknownStrategy("partition-10".to_string()))
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use super::candidates::CandidateLists;
+use super::local_search::LocalSearch;
+use super::metric::Metric;
+use super::or_opt::{MAX_SEGMENT_LEN, move_segment};
+use super::path::EdgeCost;
+use super::tour_array::TourArray;
+use super::tour_strategy::{
+    NearestNeighborStrategy, SimulatedAnnealingStrategy, Tour, TourConfig, TourError, TourStrategy,
+};
+use super::turn::turn_delta;
+use super::utils::on_distinct_positions;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+const NEIGHBORS: usize = 8;
+
+/// Probability of accepting an uphill move of average size at the start.
+const INITIAL_ACCEPTANCE: f32 = 0.1;
+
+/// Ratio between the final and the initial temperature.
+const FINAL_TEMPERATURE_RATIO: f32 = 1e-3;
+
+/// Minimum time between two progress reports.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Snapshot of a running annealing, passed to the progress callback.
+#[derive(Clone, Debug)]
+pub struct AnnealingProgress {
+    pub elapsed: Duration,
+    pub moves: usize,
+    pub temperature: f32,
+    pub tour_length: f32,
+    pub best_length: f32,
+}
+
+pub type ProgressCallback = Box<dyn Fn(&AnnealingProgress) + Send + Sync>;
+
+impl Default for SimulatedAnnealingStrategy {
+    fn default() -> Self {
+        Self {
+            default_time_budget: Duration::from_secs(5),
+            moves: None,
+            progress: None,
+        }
+    }
+}
+
+impl TourStrategy for SimulatedAnnealingStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        on_distinct_positions(points, |points| {
+            let initial_config = TourConfig {
+                time_budget: None,
+                ..config.clone()
+            };
+            let initial: Tour = NearestNeighborStrategy.build_tour(points, &initial_config)?;
+            let n: usize = points.len();
+            if n < 8 {
+                return Ok(initial);
+            }
+
+            let start: Instant = Instant::now();
+            let time_budget: Duration = config.time_budget.unwrap_or(self.default_time_budget);
+            let metric: Metric = config.metric;
+            let cost = EdgeCost::from_config(points, config);
+            let nodes: Vec<usize> = cost.to_nodes(initial.order());
+            let candidates = CandidateLists::from_nodes(&cost, &nodes, NEIGHBORS);
+            let mut rng: StdRng = StdRng::seed_from_u64(config.seed);
+            let mut annealer = Annealer {
+                cost: &cost,
+                candidates: &candidates,
+                tour: TourArray::new(&nodes, cost.num_nodes()),
+            };
+
+            let initial_temperature: f32 = annealer.initial_temperature(&mut rng);
+            let mut temperature: f32 = initial_temperature;
+            let mut length: f32 = cost.tour_length(annealer.tour.order());
+            let mut best_length: f32 = length;
+            let mut best_order: Vec<usize> = nodes;
+            let batch: usize = n.max(10_000);
+            let mut moves: usize = 0;
+            let mut last_report: Instant = start;
+
+            loop {
+                for _ in 0..batch {
+                    annealer.random_move(temperature, &mut rng);
+                }
+                moves += batch;
+
+                length = cost.tour_length(annealer.tour.order());
+                if length < best_length {
+                    best_length = length;
+                    best_order.copy_from_slice(annealer.tour.order());
+                }
+
+                let elapsed: Duration = start.elapsed();
+                if let Some(progress) = &self.progress
+                    && last_report.elapsed() >= PROGRESS_INTERVAL
+                {
+                    last_report = Instant::now();
+                    progress(&AnnealingProgress {
+                        elapsed,
+                        moves,
+                        temperature,
+                        tour_length: length,
+                        best_length,
+                    });
+                }
+
+                let fraction: f32 = match self.moves {
+                    Some(total) => moves as f32 / total.max(1) as f32,
+                    None => elapsed.as_secs_f32() / time_budget.as_secs_f32().max(f32::EPSILON),
+                };
+                if fraction >= 1.0 || elapsed >= time_budget {
+                    break;
+                }
+                temperature = initial_temperature * FINAL_TEMPERATURE_RATIO.powf(fraction);
+            }
+
+            let mut order: Vec<usize> = if length <= best_length {
+                annealer.tour.into_order()
+            } else {
+                best_order
+            };
+            LocalSearch {
+                metric,
+                ..LocalSearch::default()
+            }
+            .optimize_with(&cost, &candidates, &mut order);
+            Ok(Tour::new(cost.from_nodes(&order)))
+        })
+    }
+}
+
+struct Annealer<'a> {
+    cost: &'a EdgeCost<'a>,
+    candidates: &'a CandidateLists,
+    tour: TourArray,
+}
+
+impl Annealer<'_> {
+    fn dist(&self, a: usize, b: usize) -> f32 {
+        self.cost.distance(a, b)
+    }
+
+    /// Temperature at which an uphill 2-opt move of average size is accepted with
+    /// probability `INITIAL_ACCEPTANCE`.
+    fn initial_temperature(&self, rng: &mut StdRng) -> f32 {
+        let mut sum: f32 = 0.0;
+        let mut count: usize = 0;
+        for _ in 0..1000 {
+            let a: usize = self.tour.at(rng.random_range(0..self.tour.len()));
+            let Some(c) = self.random_candidate(a, rng) else {
+                continue;
+            };
+            let b: usize = self.tour.next(a);
+            let d: usize = self.tour.next(c);
+            // Moves touching the ends of an open path may break the fixed edge.
+            if [a, b, c, d].iter().any(|&node| self.cost.is_virtual(node)) {
+                continue;
+            }
+            let delta: f32 = self.dist(a, c) + self.dist(b, d) - self.dist(a, b) - self.dist(c, d);
+            if delta > 0.0 {
+                sum += delta;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return f32::EPSILON;
+        }
+        (sum / count as f32) / -INITIAL_ACCEPTANCE.ln()
+    }
+
+    fn random_candidate(&self, city: usize, rng: &mut StdRng) -> Option<usize> {
+        let list: &[usize] = self.candidates.of(city);
+        if list.is_empty() {
+            None
+        } else {
+            Some(list[rng.random_range(0..list.len())])
+        }
+    }
+
+    fn accept(&self, delta: f32, temperature: f32, rng: &mut StdRng) -> bool {
+        delta < 0.0 || rng.random::<f32>() < (-delta / temperature).exp()
+    }
+
+    /// Proposes a random 2-opt or Or-opt move towards a candidate neighbor and applies
+    /// it if the Metropolis criterion accepts it.
+    fn random_move(&mut self, temperature: f32, rng: &mut StdRng) {
+        let a: usize = self.tour.at(rng.random_range(0..self.tour.len()));
+        let Some(c) = self.random_candidate(a, rng) else {
+            return;
+        };
+        let forward: bool = rng.random_bool(0.5);
+
+        if rng.random_bool(0.5) {
+            let b: usize = self.tour.step(a, forward);
+            let d: usize = self.tour.step(c, forward);
+            if c == b || d == a {
+                return;
+            }
+            let delta: f32 = self.dist(a, c) + self.dist(b, d) - self.dist(a, b) - self.dist(c, d)
+                + turn_delta(self.cost, &self.tour, &[(a, b), (c, d)], &[(a, c), (b, d)]);
+            if self.accept(delta, temperature, rng) {
+                self.tour.two_opt_move(a, b, c, d);
+            }
+            return;
+        }
+
+        let segment_len: usize = rng.random_range(1..=MAX_SEGMENT_LEN);
+        if self.tour.len() < segment_len + 3 {
+            return;
+        }
+        let s1: usize = a;
+        let mut segment: [usize; MAX_SEGMENT_LEN] = [s1; MAX_SEGMENT_LEN];
+        for i in 1..segment_len {
+            segment[i] = self.tour.step(segment[i - 1], forward);
+        }
+        let segment: &[usize] = &segment[..segment_len];
+        let s2: usize = segment[segment_len - 1];
+        let p: usize = self.tour.step(s1, !forward);
+        let nx: usize = self.tour.step(s2, forward);
+        let d: usize = if rng.random_bool(0.5) {
+            self.tour.next(c)
+        } else {
+            self.tour.prev(c)
+        };
+        if segment.contains(&c) || segment.contains(&d) {
+            return;
+        }
+
+        let delta: f32 = self.dist(p, nx) + self.dist(c, s1) + self.dist(s2, d)
+            - self.dist(p, s1)
+            - self.dist(s2, nx)
+            - self.dist(c, d)
+            + turn_delta(
+                self.cost,
+                &self.tour,
+                &[(p, s1), (s2, nx), (c, d)],
+                &[(p, nx), (c, s1), (s2, d)],
+            );
+        if self.accept(delta, temperature, rng) {
+            move_segment(&mut self.tour, forward, p, s1, s2, nx, c, d, s1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anneal(points: &[(f32, f32)], seed: u64) -> Tour {
+        let config = TourConfig {
+            seed,
+            time_budget: Some(Duration::from_secs(60)),
+            ..TourConfig::default()
+        };
+        SimulatedAnnealingStrategy {
+            moves: Some(100_000),
+            ..SimulatedAnnealingStrategy::default()
+        }
+        .build_tour(points, &config)
+        .unwrap()
+    }
+
+    #[test]
+    fn fixed_move_count_reproduces_the_tour() {
+        let mut rng: StdRng = StdRng::seed_from_u64(0);
+        let points: Vec<(f32, f32)> = (0..300)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect();
+        let tour: Tour = anneal(&points, 1);
+        assert_eq!(anneal(&points, 1), tour);
+        assert_ne!(anneal(&points, 2), tour);
+    }
+}
@@ -0,0 +1,135 @@
+use super::path::EdgeCost;
+use super::tour_strategy::{HilbertCurveStrategy, Tour, TourConfig, TourError, TourStrategy};
+use rayon::prelude::*;
+
+/// Cells per axis of the grid the points are snapped to before computing their curve
+/// index.
+const GRID_SIDE: u32 = 1 << 16;
+
+impl TourStrategy for HilbertCurveStrategy {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError> {
+        if points.is_empty() {
+            return Err(TourError::EmptyInput);
+        }
+
+        let (min_x, max_x, min_y, max_y) = points.iter().fold(
+            (
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+            ),
+            |(min_x, max_x, min_y, max_y), &(x, y)| {
+                (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+            },
+        );
+        // A square cell keeps the curve's locality on non-square canvases.
+        let side: f32 = (max_x - min_x).max(max_y - min_y).max(f32::EPSILON);
+        let scale: f32 = (GRID_SIDE - 1) as f32 / side;
+
+        let mut keyed: Vec<(u64, usize)> = points
+            .par_iter()
+            .enumerate()
+            .map(|(idx, &(x, y))| {
+                let gx: u32 = ((x - min_x) * scale) as u32;
+                let gy: u32 = ((y - min_y) * scale) as u32;
+                (hilbert_index(gx, gy), idx)
+            })
+            .collect();
+        keyed.par_sort_unstable();
+
+        let mut tour: Vec<usize> = keyed.into_iter().map(|(_, idx)| idx).collect();
+        pin_ends(&EdgeCost::from_config(points, config), &mut tour);
+        Ok(Tour::new(tour))
+    }
+}
+
+/// Turns the closed curve into an open path when `cost` asks for one. Without a local
+/// search to pull the path towards its pinned ends, [`EdgeCost::cut`] would rather drop
+/// the long jump that closes the curve, so the path is made to start and end at the
+/// points nearest to the pinned ends instead.
+fn pin_ends(cost: &EdgeCost, tour: &mut [usize]) {
+    let Some(path) = cost.path() else {
+        return;
+    };
+    if tour.len() < 2 {
+        return;
+    }
+    let points: &[(f32, f32)] = cost.points();
+    let nearest = |tour: &[usize], anchor: (f32, f32)| -> usize {
+        let distance = |idx: &usize| cost.metric().distance(anchor, points[*idx]);
+        *tour
+            .iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(&tour[0])
+    };
+
+    match (path.start, path.end) {
+        (None, None) => cost.cut(tour),
+        (Some(start), None) => {
+            let first: usize = nearest(tour, start);
+            start_at(cost, tour, first);
+        }
+        (None, Some(end)) => {
+            let last: usize = nearest(tour, end);
+            start_at(cost, tour, last);
+            tour.reverse();
+        }
+        (Some(start), Some(end)) => {
+            let (first, last) = (nearest(tour, start), nearest(tour, end));
+            if first == last {
+                start_at(cost, tour, first);
+                return;
+            }
+            // s x1 .. xm e y1 .. yk becomes s x1 .. xm yk .. y1 e, either along the
+            // curve or against it, whichever is shorter.
+            let position = |tour: &[usize], idx: usize| tour.iter().position(|&i| i == idx);
+            tour.rotate_left(position(tour, first).unwrap_or(0));
+            let mut backward: Vec<usize> = tour.to_vec();
+            backward[1..].reverse();
+            for order in [&mut *tour, &mut backward[..]] {
+                let e: usize = position(order, last).unwrap_or(0);
+                order[e..].reverse();
+            }
+            let length = |order: &[usize]| cost.tour_length(&cost.to_nodes(order));
+            if length(&backward) < length(tour) {
+                tour.copy_from_slice(&backward);
+            }
+        }
+    }
+}
+
+/// Rotates the closed `tour` to start at the point `first`, walking it in the
+/// direction that drops the longer of the two edges at `first`.
+fn start_at(cost: &EdgeCost, tour: &mut [usize], first: usize) {
+    let len: usize = tour.len();
+    let i: usize = tour.iter().position(|&idx| idx == first).unwrap_or(0);
+    let (prev, next) = (tour[(i + len - 1) % len], tour[(i + 1) % len]);
+    if cost.distance(prev, first) >= cost.distance(first, next) {
+        tour.rotate_left(i);
+    } else {
+        tour.rotate_left((i + 1) % len);
+        tour.reverse();
+    }
+}
+
+/// Position of the grid cell (x, y) along the Hilbert curve filling the
+/// `GRID_SIDE` x `GRID_SIDE` grid.
+fn hilbert_index(mut x: u32, mut y: u32) -> u64 {
+    let mut index: u64 = 0;
+    let mut s: u32 = GRID_SIDE / 2;
+    while s > 0 {
+        let rx: u32 = (x & s > 0) as u32;
+        let ry: u32 = (y & s > 0) as u32;
+        index += (s as u64) * (s as u64) * ((3 * rx) ^ ry) as u64;
+        if ry == 0 {
+            if rx == 1 {
+                x = GRID_SIDE - 1 - x;
+                y = GRID_SIDE - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
@@ -0,0 +1,175 @@
+use super::intersections::count_self_intersections;
+use super::metric::Metric;
+use super::tour_strategy::TourError;
+use super::turn::turning_angle;
+
+/// Number of equal-width bins in the edge length histogram.
+pub const HISTOGRAM_BINS: usize = 10;
+
+/// Checks that `tour` visits every index of `0..num_points` exactly once.
+pub fn validate_tour(num_points: usize, tour: &[usize]) -> Result<(), TourError> {
+    let mut visited: Vec<bool> = vec![false; num_points];
+    for &idx in tour {
+        if idx >= num_points {
+            return Err(TourError::IndexOutOfRange {
+                index: idx,
+                len: num_points,
+            });
+        }
+        if visited[idx] {
+            return Err(TourError::DuplicateIndex(idx));
+        }
+        visited[idx] = true;
+    }
+    match visited.iter().position(|&seen| !seen) {
+        Some(idx) => Err(TourError::MissingIndex(idx)),
+        None => Ok(()),
+    }
+}
+
+/// Quality statistics of a validated tour.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TourStats {
+    pub length: f32,
+    pub mean_edge: f32,
+    pub max_edge: f32,
+    /// Number of edges per length bin, from 0 up to `max_edge`.
+    pub histogram: Vec<usize>,
+    pub histogram_bin_width: f32,
+    /// Number of pairs of non-adjacent edges that cross or touch.
+    pub self_intersections: usize,
+    /// Turning angles in radians at the points between two edges.
+    pub mean_turn_angle: f32,
+    pub max_turn_angle: f32,
+}
+
+impl TourStats {
+    /// Validates `tour` and computes its statistics under `metric`. An open path
+    /// (`closed` false) has no edge back to its first point.
+    pub fn new(
+        points: &[(f32, f32)],
+        tour: &[usize],
+        metric: Metric,
+        closed: bool,
+    ) -> Result<Self, TourError> {
+        validate_tour(points.len(), tour)?;
+        if tour.is_empty() {
+            return Err(TourError::EmptyInput);
+        }
+
+        let num_edges: usize = if closed { tour.len() } else { tour.len() - 1 };
+        let edges: Vec<f32> = (0..num_edges)
+            .map(|i| metric.distance(points[tour[i]], points[tour[(i + 1) % tour.len()]]))
+            .collect();
+        let length: f32 = edges.iter().sum();
+        let max_edge: f32 = edges.iter().copied().fold(0.0, f32::max);
+
+        let histogram_bin_width: f32 = max_edge / HISTOGRAM_BINS as f32;
+        let mut histogram: Vec<usize> = vec![0; HISTOGRAM_BINS];
+        for &edge in &edges {
+            let bin: usize = if histogram_bin_width > 0.0 {
+                ((edge / histogram_bin_width) as usize).min(HISTOGRAM_BINS - 1)
+            } else {
+                0
+            };
+            histogram[bin] += 1;
+        }
+
+        let n: usize = tour.len();
+        let turns: Vec<f32> = if n < 3 {
+            Vec::new()
+        } else {
+            let inner = if closed { 0..n } else { 1..n - 1 };
+            inner
+                .map(|i| {
+                    let prev: (f32, f32) = points[tour[(i + n - 1) % n]];
+                    let next: (f32, f32) = points[tour[(i + 1) % n]];
+                    turning_angle(prev, points[tour[i]], next)
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            length,
+            mean_edge: length / edges.len().max(1) as f32,
+            max_edge,
+            histogram,
+            histogram_bin_width,
+            self_intersections: count_self_intersections(points, tour, closed),
+            mean_turn_angle: turns.iter().sum::<f32>() / turns.len().max(1) as f32,
+            max_turn_angle: turns.iter().copied().fold(0.0, f32::max),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_tour_reports_the_first_problem() {
+        assert_eq!(validate_tour(4, &[2, 0, 3, 1]), Ok(()));
+        assert_eq!(validate_tour(0, &[]), Ok(()));
+        assert_eq!(
+            validate_tour(4, &[2, 0, 2, 1]),
+            Err(TourError::DuplicateIndex(2))
+        );
+        assert_eq!(
+            validate_tour(4, &[3, 0, 1]),
+            Err(TourError::MissingIndex(2))
+        );
+        assert_eq!(
+            validate_tour(3, &[0, 1, 3]),
+            Err(TourError::IndexOutOfRange { index: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    fn stats_of_a_rectangle() {
+        // A 4 by 2 rectangle with an extra point halfway along the bottom.
+        let points = [(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)];
+        let tour = [0, 1, 2, 3, 4];
+        let stats = TourStats::new(&points, &tour, Metric::Euclidean, true).unwrap();
+        assert_eq!(stats.length, 12.0);
+        assert_eq!(stats.mean_edge, 12.0 / 5.0);
+        assert_eq!(stats.max_edge, 4.0);
+        assert_eq!(stats.histogram_bin_width, 0.4);
+        assert_eq!(stats.histogram.iter().sum::<usize>(), 5);
+        assert_eq!(stats.histogram[HISTOGRAM_BINS - 1], 1);
+        assert_eq!(stats.histogram[5], 4);
+        assert_eq!(stats.self_intersections, 0);
+        // Four corners turn by a right angle and the middle point goes straight on.
+        let right: f32 = std::f32::consts::FRAC_PI_2;
+        assert!((stats.mean_turn_angle - 4.0 * right / 5.0).abs() < 1e-6);
+        assert!((stats.max_turn_angle - right).abs() < 1e-6);
+
+        // The open path leaves out the closing edge and the turns at its ends.
+        let stats = TourStats::new(&points, &tour, Metric::Euclidean, false).unwrap();
+        assert_eq!(stats.length, 10.0);
+        assert_eq!(stats.histogram.iter().sum::<usize>(), 4);
+        assert!((stats.mean_turn_angle - 2.0 * right / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stats_count_crossings_but_not_coincident_points() {
+        let points = [(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0), (0.0, 2.0)];
+        let stats = TourStats::new(&points, &[0, 1, 2, 3, 4], Metric::Euclidean, true).unwrap();
+        assert_eq!(stats.self_intersections, 1);
+        let stats = TourStats::new(&points, &[0, 2, 1, 3, 4], Metric::Euclidean, true).unwrap();
+        assert_eq!(stats.self_intersections, 0);
+        assert_eq!(stats.histogram[0], 1);
+    }
+
+    #[test]
+    fn stats_reject_invalid_tours() {
+        let points = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)];
+        assert_eq!(
+            TourStats::new(&points, &[0, 1], Metric::Euclidean, true),
+            Err(TourError::MissingIndex(2))
+        );
+        assert_eq!(
+            TourStats::new(&[], &[], Metric::Euclidean, true),
+            Err(TourError::EmptyInput)
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use super::metric::Metric;
+
+/// Maximum number of 2-opt passes over the strokes in [`order_strokes`]. Every pass
+/// takes O(k²) for k strokes, and most of the travel is saved in the first few.
+const MAX_PASSES: usize = 20;
+
+/// Edges longer than the threshold are lifted out of a tour, splitting it into
+/// separate strokes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JumpThreshold {
+    /// Maximum length of a drawn edge.
+    Absolute(f32),
+    /// Maximum length of a drawn edge as a multiple of the median edge length.
+    MedianMultiple(f32),
+}
+
+impl JumpThreshold {
+    /// Maximum drawn edge length given the lengths of all edges.
+    pub fn max_edge(&self, edges: &[f32]) -> f32 {
+        match *self {
+            JumpThreshold::Absolute(length) => length,
+            JumpThreshold::MedianMultiple(factor) => {
+                if edges.is_empty() {
+                    return f32::INFINITY;
+                }
+                let mut sorted: Vec<f32> = edges.to_vec();
+                let middle: usize = sorted.len() / 2;
+                let (_, median, _) = sorted.select_nth_unstable_by(middle, f32::total_cmp);
+                factor * *median
+            }
+        }
+    }
+}
+
+/// Cuts `tour` at every edge longer than `threshold`. Strokes are open polylines: a
+/// closed tour without long edges becomes a single stroke that ends at its first
+/// point.
+pub fn split_strokes(
+    points: &[(f32, f32)],
+    tour: &[usize],
+    metric: Metric,
+    threshold: JumpThreshold,
+    closed: bool,
+) -> Vec<Vec<usize>> {
+    let n: usize = tour.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    if n == 1 {
+        return vec![tour.to_vec()];
+    }
+    let num_edges: usize = if closed { n } else { n - 1 };
+    let edges: Vec<f32> = (0..num_edges)
+        .map(|i| metric.distance(points[tour[i]], points[tour[(i + 1) % n]]))
+        .collect();
+    let max_edge: f32 = threshold.max_edge(&edges);
+    let is_jump = |i: usize| i < num_edges && edges[i] > max_edge;
+
+    // A closed tour starts right after one of its jumps, so no stroke wraps around.
+    let first: usize = match (closed, (0..num_edges).find(|&i| is_jump(i))) {
+        (true, Some(jump)) => jump + 1,
+        (true, None) => {
+            let mut stroke: Vec<usize> = tour.to_vec();
+            stroke.push(tour[0]);
+            return vec![stroke];
+        }
+        (false, _) => 0,
+    };
+
+    let mut strokes: Vec<Vec<usize>> = Vec::new();
+    let mut stroke: Vec<usize> = Vec::new();
+    for offset in 0..n {
+        let i: usize = (first + offset) % n;
+        stroke.push(tour[i]);
+        if is_jump(i) {
+            strokes.push(std::mem::take(&mut stroke));
+        }
+    }
+    if !stroke.is_empty() {
+        strokes.push(stroke);
+    }
+    strokes
+}
+
+/// Total length of the pen-up moves from the end of each stroke to the start of the
+/// next one.
+pub fn pen_up_travel(points: &[(f32, f32)], strokes: &[Vec<usize>], metric: Metric) -> f32 {
+    strokes.windows(2).fold(0.0, |travel, pair| {
+        travel + metric.distance(points[end(&pair[0])], points[start(&pair[1])])
+    })
+}
+
+/// Reorders and reverses strokes to shorten the pen-up travel between them, by 2-opt
+/// over the sequence of strokes until a local optimum or `MAX_PASSES` passes.
+/// Reversing a run of strokes also reverses the direction every stroke of the run is
+/// drawn in.
+pub fn order_strokes(points: &[(f32, f32)], strokes: &mut [Vec<usize>], metric: Metric) {
+    let k: usize = strokes.len();
+    let dist = |a: usize, b: usize| metric.distance(points[a], points[b]);
+    for _ in 0..MAX_PASSES {
+        let mut improved: bool = false;
+        for i in 0..k {
+            let prev: Option<usize> = i.checked_sub(1).map(|p| end(&strokes[p]));
+            let first: usize = start(&strokes[i]);
+            for j in i..k {
+                let last: usize = end(&strokes[j]);
+                let next: Option<usize> = strokes.get(j + 1).map(|stroke| start(stroke));
+                let removed: f32 =
+                    prev.map_or(0.0, |p| dist(p, first)) + next.map_or(0.0, |nx| dist(last, nx));
+                let added: f32 =
+                    prev.map_or(0.0, |p| dist(p, last)) + next.map_or(0.0, |nx| dist(first, nx));
+                if added < removed - f32::EPSILON * removed {
+                    strokes[i..=j].reverse();
+                    for stroke in &mut strokes[i..=j] {
+                        stroke.reverse();
+                    }
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+fn start(stroke: &[usize]) -> usize {
+    stroke[0]
+}
+
+fn end(stroke: &[usize]) -> usize {
+    stroke[stroke.len() - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Points 0..n along the x axis with the gaps `gaps` between them.
+    fn line(gaps: &[f32]) -> Vec<(f32, f32)> {
+        let mut x: f32 = 0.0;
+        let mut points: Vec<(f32, f32)> = vec![(0.0, 0.0)];
+        for &gap in gaps {
+            x += gap;
+            points.push((x, 0.0));
+        }
+        points
+    }
+
+    #[test]
+    fn split_keeps_edges_at_the_threshold() {
+        let points: Vec<(f32, f32)> = line(&[1.0, 2.0, 1.0, 3.0, 1.0]);
+        let tour: Vec<usize> = (0..points.len()).collect();
+        let split = |threshold: JumpThreshold| {
+            split_strokes(&points, &tour, Metric::Euclidean, threshold, false)
+        };
+        assert_eq!(
+            split(JumpThreshold::Absolute(2.0)),
+            vec![vec![0, 1, 2, 3], vec![4, 5]]
+        );
+        assert_eq!(
+            split(JumpThreshold::Absolute(1.5)),
+            vec![vec![0, 1], vec![2, 3], vec![4, 5]]
+        );
+        assert_eq!(split(JumpThreshold::Absolute(3.0)), vec![tour.clone()]);
+        // The median edge is 1, so twice the median cuts only the edge of 3.
+        assert_eq!(
+            split(JumpThreshold::MedianMultiple(2.0)),
+            vec![vec![0, 1, 2, 3], vec![4, 5]]
+        );
+    }
+
+    #[test]
+    fn split_closed_tour_never_wraps_a_stroke() {
+        // The edge back from the last point to the first is 8 long.
+        let points: Vec<(f32, f32)> = line(&[1.0, 5.0, 1.0, 1.0]);
+        let tour: Vec<usize> = (0..points.len()).collect();
+        let split = |max: f32| {
+            split_strokes(
+                &points,
+                &tour,
+                Metric::Euclidean,
+                JumpThreshold::Absolute(max),
+                true,
+            )
+        };
+        assert_eq!(split(4.0), vec![vec![2, 3, 4], vec![0, 1]]);
+        assert_eq!(split(6.0), vec![vec![0, 1, 2, 3, 4]]);
+        assert_eq!(split(8.0), vec![vec![0, 1, 2, 3, 4, 0]]);
+    }
+
+    #[test]
+    fn ordering_never_adds_travel() {
+        for seed in 0..20 {
+            let mut rng: StdRng = StdRng::seed_from_u64(seed);
+            let points: Vec<(f32, f32)> = (0..200)
+                .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+                .collect();
+            let mut order: Vec<usize> = (0..points.len()).collect();
+            order.shuffle(&mut rng);
+            let mut strokes: Vec<Vec<usize>> = order
+                .chunks(rng.random_range(1..10))
+                .map(|chunk| chunk.to_vec())
+                .collect();
+            let before: f32 = pen_up_travel(&points, &strokes, Metric::Euclidean);
+            let drawn: Vec<Vec<usize>> = undirected(&strokes);
+            order_strokes(&points, &mut strokes, Metric::Euclidean);
+            assert!(pen_up_travel(&points, &strokes, Metric::Euclidean) <= before);
+            // The same strokes are drawn, some of them backwards.
+            assert_eq!(undirected(&strokes), drawn);
+        }
+    }
+
+    /// The strokes in either direction, sorted.
+    fn undirected(strokes: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut undirected: Vec<Vec<usize>> = strokes
+            .iter()
+            .map(|stroke| {
+                let reversed: Vec<usize> = stroke.iter().rev().copied().collect();
+                stroke.clone().min(reversed)
+            })
+            .collect();
+        undirected.sort();
+        undirected
+    }
+
+    #[test]
+    fn travel_is_measured_between_strokes() {
+        let points: Vec<(f32, f32)> = line(&[1.0, 2.0, 4.0]);
+        let strokes: Vec<Vec<usize>> = vec![vec![0, 1], vec![2], vec![3]];
+        assert_eq!(pen_up_travel(&points, &strokes, Metric::Euclidean), 6.0);
+        assert_eq!(
+            pen_up_travel(&points, &strokes[..1], Metric::Euclidean),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,198 @@
+/// Array representation of a closed tour with an inverse position index, so that
+/// successor/predecessor queries are O(1) and segment reversals touch only the
+/// shorter side of the cycle.
+pub struct TourArray {
+    order: Vec<usize>,
+    pos: Vec<usize>,
+}
+
+impl TourArray {
+    /// `num_points` must be larger than every city index in `tour`.
+    pub fn new(tour: &[usize], num_points: usize) -> Self {
+        let mut pos: Vec<usize> = vec![usize::MAX; num_points];
+        for (i, &city) in tour.iter().enumerate() {
+            pos[city] = i;
+        }
+        Self {
+            order: tour.to_vec(),
+            pos,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn position(&self, city: usize) -> usize {
+        self.pos[city]
+    }
+
+    pub fn at(&self, position: usize) -> usize {
+        self.order[position % self.order.len()]
+    }
+
+    pub fn next(&self, city: usize) -> usize {
+        let n: usize = self.order.len();
+        self.order[(self.pos[city] + 1) % n]
+    }
+
+    pub fn prev(&self, city: usize) -> usize {
+        let n: usize = self.order.len();
+        self.order[(self.pos[city] + n - 1) % n]
+    }
+
+    /// Successor of `city` when walking the tour forward, predecessor otherwise.
+    pub fn step(&self, city: usize, forward: bool) -> usize {
+        if forward {
+            self.next(city)
+        } else {
+            self.prev(city)
+        }
+    }
+
+    /// Returns true if `b` lies on the forward path from `a` to `c` (inclusive).
+    pub fn between(&self, a: usize, b: usize, c: usize) -> bool {
+        let n: usize = self.order.len();
+        let pa: usize = self.pos[a];
+        let offset_b: usize = (self.pos[b] + n - pa) % n;
+        let offset_c: usize = (self.pos[c] + n - pa) % n;
+        offset_b <= offset_c
+    }
+
+    /// Reverses the forward path from city `from` to city `to`. As the tour is a
+    /// cycle, the complementary path is reversed instead when it is shorter.
+    pub fn reverse(&mut self, from: usize, to: usize) {
+        let n: usize = self.order.len();
+        let mut i: usize = self.pos[from];
+        let mut j: usize = self.pos[to];
+        let mut len: usize = (j + n - i) % n + 1;
+        if 2 * len > n {
+            let new_i: usize = (j + 1) % n;
+            j = (i + n - 1) % n;
+            i = new_i;
+            len = n - len;
+        }
+
+        for _ in 0..len / 2 {
+            let a: usize = self.order[i];
+            let b: usize = self.order[j];
+            self.order[i] = b;
+            self.pos[b] = i;
+            self.order[j] = a;
+            self.pos[a] = j;
+            i = (i + 1) % n;
+            j = (j + n - 1) % n;
+        }
+    }
+
+    /// Replaces edges (a, b) and (c, d) with (a, c) and (b, d). Both edges must point
+    /// in the same direction, i.e. `b` follows `a` exactly when `d` follows `c`.
+    pub fn two_opt_move(&mut self, a: usize, b: usize, c: usize, d: usize) {
+        if self.next(a) == b {
+            debug_assert_eq!(self.next(c), d);
+            self.reverse(b, c);
+        } else {
+            debug_assert_eq!(self.prev(a), b);
+            debug_assert_eq!(self.prev(c), d);
+            self.reverse(a, d);
+        }
+    }
+
+    pub fn into_order(self) -> Vec<usize> {
+        self.order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// Unordered neighbors of every city, which ignores where the cycle starts and in
+    /// which direction it is stored.
+    fn neighbors(order: &[usize]) -> Vec<[usize; 2]> {
+        let n: usize = order.len();
+        let mut neighbors: Vec<[usize; 2]> = vec![[0; 2]; n];
+        for i in 0..n {
+            let (a, b) = (order[(i + n - 1) % n], order[(i + 1) % n]);
+            neighbors[order[i]] = [a.min(b), a.max(b)];
+        }
+        neighbors
+    }
+
+    fn assert_consistent(tour: &TourArray) {
+        for (i, &city) in tour.order().iter().enumerate() {
+            assert_eq!(tour.position(city), i);
+            assert_eq!(tour.at(i), city);
+        }
+    }
+
+    #[test]
+    fn reverse_matches_reversing_the_forward_path() {
+        let mut rng: StdRng = StdRng::seed_from_u64(5);
+        for n in [3, 4, 5, 8, 13, 50] {
+            let mut cities: Vec<usize> = (0..n).collect();
+            cities.shuffle(&mut rng);
+            let mut tour = TourArray::new(&cities, n);
+            for _ in 0..200 {
+                let (from, to) = (rng.random_range(0..n), rng.random_range(0..n));
+                // The complement may be reversed instead, which mirrors the direction
+                // of the cycle, so every step is checked against the order before it.
+                let mut expected: Vec<usize> = tour.order().to_vec();
+                tour.reverse(from, to);
+
+                let start: usize = expected.iter().position(|&city| city == from).unwrap();
+                expected.rotate_left(start);
+                let end: usize = expected.iter().position(|&city| city == to).unwrap();
+                expected[..=end].reverse();
+
+                assert_consistent(&tour);
+                assert_eq!(neighbors(tour.order()), neighbors(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn reverse_flips_the_shorter_side() {
+        let mut tour = TourArray::new(&[0, 1, 2, 3, 4, 5, 6, 7], 8);
+        tour.reverse(1, 6);
+        assert_eq!(tour.order(), &[7, 1, 2, 3, 4, 5, 6, 0]);
+        assert_consistent(&tour);
+        tour.reverse(2, 3);
+        assert_eq!(tour.order(), &[7, 1, 3, 2, 4, 5, 6, 0]);
+        assert_consistent(&tour);
+    }
+
+    #[test]
+    fn two_opt_move_replaces_the_edges() {
+        for (a, b, c, d) in [(1, 2, 5, 6), (2, 1, 6, 5), (6, 7, 2, 3)] {
+            let mut tour = TourArray::new(&[0, 1, 2, 3, 4, 5, 6, 7], 8);
+            tour.two_opt_move(a, b, c, d);
+            assert_consistent(&tour);
+            assert!(tour.next(a) == c || tour.prev(a) == c);
+            assert!(tour.next(b) == d || tour.prev(b) == d);
+            assert!(tour.next(a) != b && tour.prev(a) != b);
+            assert!(tour.next(c) != d && tour.prev(c) != d);
+        }
+    }
+
+    #[test]
+    fn between_follows_the_forward_direction() {
+        let tour = TourArray::new(&[3, 0, 4, 1, 2], 5);
+        assert!(tour.between(0, 1, 2));
+        assert!(tour.between(1, 3, 0));
+        assert!(!tour.between(1, 0, 3));
+        assert!(tour.between(4, 4, 4));
+        assert_eq!((tour.next(2), tour.prev(3)), (3, 2));
+    }
+}
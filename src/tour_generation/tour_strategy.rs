@@ -1,20 +1,163 @@
-use geo::Point;
-pub trait TourStrategy {
-    fn build_tour(&self, points: &Vec<(f32, f32)>, hull_points: &Vec<Point<f32>>) -> Vec<usize>;
+use super::brightness::BrightnessCost;
+use super::metric::Metric;
+use super::path::PathEnds;
+use super::simulated_annealing::ProgressCallback;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings shared by every tour strategy.
+#[derive(Clone, Debug)]
+pub struct TourConfig {
+    /// Seed for strategies that make random choices.
+    pub seed: u64,
+    /// Upper bound on the time spent improving the tour.
+    pub time_budget: Option<Duration>,
+    pub metric: Metric,
+    /// Build an open path with these ends instead of a closed tour.
+    pub path: Option<PathEnds>,
+    /// Penalty added to the metric for edges that cross bright parts of the image.
+    pub brightness: Option<Arc<BrightnessCost>>,
+    /// Penalty per radian of turning at each point, used by the tour optimizers.
+    pub turn_penalty: f32,
+}
+
+impl Default for TourConfig {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            time_budget: None,
+            metric: Metric::default(),
+            path: None,
+            brightness: None,
+            turn_penalty: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TourError {
+    /// There are no points to visit.
+    EmptyInput,
+    /// No strategy is registered under this name.
+    UnknownStrategy(String),
+    /// The tour visits an index that is not a point.
+    IndexOutOfRange { index: usize, len: usize },
+    /// The tour visits this point more than once.
+    DuplicateIndex(usize),
+    /// The tour never visits this point.
+    MissingIndex(usize),
 }
 
-pub struct Tour<T: TourStrategy> {
-    tour_strategy: T,
+impl fmt::Display for TourError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TourError::EmptyInput => write!(f, "no points to build a tour from"),
+            TourError::UnknownStrategy(name) => write!(f, "unknown tour strategy '{}'", name),
+            TourError::IndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "tour visits index {} but there are {} points",
+                    index, len
+                )
+            }
+            TourError::DuplicateIndex(index) => write!(f, "tour visits point {} twice", index),
+            TourError::MissingIndex(index) => write!(f, "tour never visits point {}", index),
+        }
+    }
+}
+
+impl std::error::Error for TourError {}
+
+/// A closed tour, or an open path when the config asks for one, given as the order in
+/// which the point indices are visited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tour {
+    order: Vec<usize>,
+}
+
+impl Tour {
+    pub fn new(order: Vec<usize>) -> Self {
+        Self { order }
+    }
+
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn order_mut(&mut self) -> &mut [usize] {
+        &mut self.order
+    }
+
+    pub fn into_order(self) -> Vec<usize> {
+        self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn length(&self, points: &[(f32, f32)], metric: Metric) -> f32 {
+        metric.tour_length(points, &self.order)
+    }
+}
+
+impl From<Vec<usize>> for Tour {
+    fn from(order: Vec<usize>) -> Self {
+        Self::new(order)
+    }
+}
+
+pub trait TourStrategy: Send + Sync {
+    fn build_tour(&self, points: &[(f32, f32)], config: &TourConfig) -> Result<Tour, TourError>;
 }
 
 pub struct GreedyStrategy;
 pub struct CheapestInsertionStrategy;
+/// Starting from the convex hull, repeatedly inserts the point farthest from the
+/// tour at its cheapest position, which settles the global shape early.
+pub struct FarthestInsertionStrategy;
+/// Starting from the convex hull, inserts the points in a random order (seeded by the
+/// config) at their cheapest position.
+pub struct RandomInsertionStrategy;
+pub struct NearestNeighborStrategy;
+/// Greedy matching of the shortest candidate edges into path fragments, which are
+/// then chained into a tour.
+pub struct GreedyEdgeStrategy;
+/// Visits the points in Hilbert curve order in O(n log n). The tour is returned
+/// without local search, so it can be used as is or as a seed for an optimizer.
+pub struct HilbertCurveStrategy;
 
-impl<T: TourStrategy> Tour<T> {
-    pub fn new(tour_strategy: T) -> Self {
-        Self { tour_strategy }
-    }
-    pub fn tour(&self, points: &Vec<(f32, f32)>, hull_points: &Vec<Point<f32>>) -> Vec<usize> {
-        self.tour_strategy.build_tour(points, hull_points)
+/// Anytime strategy that keeps applying random 2-opt and Or-opt moves under a
+/// simulated annealing schedule until the time budget runs out.
+pub struct SimulatedAnnealingStrategy {
+    /// Time budget used when the config does not set one.
+    pub default_time_budget: Duration,
+    /// Fixed number of moves to anneal for. The schedule then only depends on the
+    /// seed, which makes the tour reproducible (unless the time budget runs out first).
+    pub moves: Option<usize>,
+    pub progress: Option<ProgressCallback>,
+}
+
+/// Splits the points into spatial cells of at most `cell_size` points, builds the tour
+/// of every cell in parallel with `cell_strategy`, and stitches the sub-tours into a
+/// single tour before a final local search.
+pub struct PartitionStrategy {
+    pub cell_strategy: Box<dyn TourStrategy>,
+    pub cell_size: usize,
+}
+
+/// Picks cheapest insertion for small point sets and greedy above `threshold` points.
+pub struct AutoStrategy {
+    pub threshold: usize,
+}
+
+impl Default for AutoStrategy {
+    fn default() -> Self {
+        Self { threshold: 2048 }
     }
 }
@@ -0,0 +1,62 @@
+use super::path::EdgeCost;
+use super::tour_array::TourArray;
+
+/// Angle in radians by which the direction changes at `b` when going from `a` to `c`
+/// through it, from 0 for going straight on to π for turning back. Zero when `b`
+/// coincides with one of its neighbors.
+pub fn turning_angle(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    let (ux, uy) = (b.0 - a.0, b.1 - a.1);
+    let (wx, wy) = (c.0 - b.0, c.1 - b.1);
+    if (ux == 0.0 && uy == 0.0) || (wx == 0.0 && wy == 0.0) {
+        return 0.0;
+    }
+    (ux * wy - uy * wx).atan2(ux * wx + uy * wy).abs()
+}
+
+/// Change of the turn penalty of `tour` when the edges `removed` are replaced by the
+/// edges `added`. Only the endpoints of these edges change their neighbors, so only
+/// their turns are evaluated.
+pub fn turn_delta(
+    cost: &EdgeCost,
+    tour: &TourArray,
+    removed: &[(usize, usize)],
+    added: &[(usize, usize)],
+) -> f32 {
+    if cost.turn_penalty() == 0.0 {
+        return 0.0;
+    }
+    let mut delta: f32 = 0.0;
+    let mut seen: Vec<usize> = Vec::with_capacity(2 * (removed.len() + added.len()));
+    for &(x, y) in removed.iter().chain(added) {
+        for v in [x, y] {
+            if seen.contains(&v) {
+                continue;
+            }
+            seen.push(v);
+
+            let old: [usize; 2] = [tour.prev(v), tour.next(v)];
+            let mut new: Vec<usize> = old.to_vec();
+            for &(a, b) in removed {
+                for (end, other) in [(a, b), (b, a)] {
+                    if end == v
+                        && let Some(i) = new.iter().position(|&u| u == other)
+                    {
+                        new.swap_remove(i);
+                    }
+                }
+            }
+            for &(a, b) in added {
+                if a == v {
+                    new.push(b);
+                } else if b == v {
+                    new.push(a);
+                }
+            }
+            debug_assert_eq!(new.len(), 2, "a move must keep every degree at 2");
+            if new.len() == 2 {
+                delta += cost.turn(new[0], v, new[1]) - cost.turn(old[0], v, old[1]);
+            }
+        }
+    }
+    delta
+}
@@ -0,0 +1,286 @@
+use super::intersections::{collapse, intersecting_edges, is_simple, segments_intersect};
+use super::path::EdgeCost;
+use super::tour_array::TourArray;
+use super::turn::turn_delta;
+use std::collections::HashMap;
+
+/// Removes self-intersections of the tour with 2-opt moves, so that it becomes a
+/// simple polygon, or a simple polyline when `cost` asks for an open path. Each move
+/// replaces two intersecting edges (a, b) and (c, d) with (a, c) and (b, d), and is
+/// only made when it lowers the tour cost under `cost`, so the repair always
+/// terminates and never undoes the work of an optimizer. Crossings are kept where
+/// removing them costs more, e.g. under a brightness or turn penalty or a metric
+/// other than the Euclidean one.
+///
+/// An open path keeps its first and last point. Consecutive points at the same
+/// position are moved as one vertex, see [`collapse`].
+///
+/// Returns whether the tour is simple afterwards, as checked by the sweep line.
+pub fn uncross(cost: &EdgeCost, tour: &mut [usize]) -> bool {
+    let points: &[(f32, f32)] = cost.points();
+    let closed: bool = cost.path().is_none();
+    let runs: Vec<usize> = collapse(points, tour, closed);
+    if runs.len() < 4 {
+        return is_simple(points, tour, closed);
+    }
+    // One vertex per run, at the position of its points.
+    let vertices: Vec<(f32, f32)> = runs.iter().map(|&i| points[tour[i]]).collect();
+    let collapsed = EdgeCost::new(
+        &vertices,
+        cost.metric(),
+        cost.path(),
+        cost.brightness(),
+        cost.turn_penalty(),
+    );
+    let order: Vec<usize> = (0..runs.len()).collect();
+    let mut repair = Repair {
+        cost: &collapsed,
+        tour: TourArray::new(&collapsed.to_nodes(&order), collapsed.num_nodes()),
+        crossing: HashMap::new(),
+    };
+    loop {
+        let order: Vec<usize> = collapsed.from_nodes(repair.tour.order());
+        let n: usize = order.len();
+        let pairs: Vec<(Edge, Edge)> = intersecting_edges(&vertices, &order, closed)
+            .into_iter()
+            .map(|(i, j)| {
+                (
+                    key(order[i], order[(i + 1) % n]),
+                    key(order[j], order[(j + 1) % n]),
+                )
+            })
+            .collect();
+        if pairs.is_empty() || !repair.round(&pairs) {
+            break;
+        }
+    }
+
+    let n: usize = tour.len();
+    let original: Vec<usize> = tour.to_vec();
+    let mut i: usize = 0;
+    for run in collapsed.from_nodes(repair.tour.order()) {
+        let (start, end) = (runs[run], runs.get(run + 1).map_or(runs[0] + n, |&end| end));
+        for position in start..end {
+            tour[i] = original[position % n];
+            i += 1;
+        }
+    }
+    is_simple(points, tour, closed)
+}
+
+type Edge = (usize, usize);
+
+struct Repair<'a> {
+    cost: &'a EdgeCost<'a>,
+    /// Node tour, see [`EdgeCost::to_nodes`].
+    tour: TourArray,
+    /// Edges known to cross each edge, keyed by its endpoints in ascending order.
+    crossing: HashMap<Edge, Vec<Edge>>,
+}
+
+impl Repair<'_> {
+    fn dist(&self, a: usize, b: usize) -> f64 {
+        self.cost.distance(a, b) as f64
+    }
+
+    /// Uncrosses the given pairs of edges. An edge that crosses several others is
+    /// replaced by the first move, so the new edges are checked right away against
+    /// the other edges it crossed. Returns whether any move was made.
+    fn round(&mut self, pairs: &[(Edge, Edge)]) -> bool {
+        self.crossing.clear();
+        let mut stack: Vec<(Edge, Edge)> = Vec::with_capacity(pairs.len());
+        for &(e, f) in pairs {
+            self.crossing.entry(e).or_default().push(f);
+            self.crossing.entry(f).or_default().push(e);
+            stack.push((e, f));
+        }
+
+        let mut moved: bool = false;
+        while let Some(((a, b), (c, d))) = stack.pop() {
+            // Earlier moves may have removed or flipped the edges.
+            let Some((a, b)) = self.forward_edge(a, b) else {
+                continue;
+            };
+            let Some((c, d)) = self.forward_edge(c, d) else {
+                continue;
+            };
+            if a == c || a == d || b == c || b == d {
+                continue;
+            }
+            let points: &[(f32, f32)] = self.cost.points();
+            if !segments_intersect(points[a], points[b], points[c], points[d]) {
+                continue;
+            }
+            let turns: f32 =
+                turn_delta(self.cost, &self.tour, &[(a, b), (c, d)], &[(a, c), (b, d)]);
+            if self.dist(a, c) + self.dist(b, d) + turns as f64 >= self.dist(a, b) + self.dist(c, d)
+            {
+                continue;
+            }
+            self.tour.two_opt_move(a, b, c, d);
+            moved = true;
+
+            let mut partners: Vec<Edge> = self.crossing.remove(&key(a, b)).unwrap_or_default();
+            partners.extend(self.crossing.remove(&key(c, d)).unwrap_or_default());
+            // Each move hands the partners on to two edges, so drop the ones that are
+            // gone or listed twice before they pile up.
+            partners.retain(|&(x, y)| self.forward_edge(x, y).is_some());
+            partners.sort_unstable();
+            partners.dedup();
+            for new_edge in [key(a, c), key(b, d)] {
+                for &partner in &partners {
+                    stack.push((new_edge, partner));
+                }
+                self.crossing
+                    .entry(new_edge)
+                    .or_default()
+                    .extend(partners.iter().copied());
+            }
+        }
+        moved
+    }
+
+    /// The edge between `a` and `b` oriented along the tour, if it is still in it.
+    fn forward_edge(&self, a: usize, b: usize) -> Option<Edge> {
+        if self.tour.next(a) == b {
+            Some((a, b))
+        } else if self.tour.next(b) == a {
+            Some((b, a))
+        } else {
+            None
+        }
+    }
+}
+
+fn key(a: usize, b: usize) -> Edge {
+    (a.min(b), a.max(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tour_generation::intersections::find_intersection;
+    use crate::tour_generation::metric::Metric;
+    use crate::tour_generation::path::PathEnds;
+    use crate::tour_generation::registry::StrategyRegistry;
+    use crate::tour_generation::tour_strategy::TourConfig;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    fn euclidean(points: &[(f32, f32)], closed: bool) -> EdgeCost<'_> {
+        let path: Option<PathEnds> = (!closed).then(PathEnds::default);
+        EdgeCost::new(points, Metric::Euclidean, path, None, 0.0)
+    }
+
+    fn assert_no_crossings(points: &[(f32, f32)], tour: &[usize], closed: bool) {
+        assert_eq!(intersecting_edges(points, tour, closed), Vec::new());
+        assert_eq!(find_intersection(points, tour, closed), None);
+        let mut visited: Vec<usize> = tour.to_vec();
+        visited.sort_unstable();
+        assert_eq!(visited, (0..points.len()).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn random_tours_become_simple() {
+        for seed in 0..5 {
+            let mut rng: StdRng = StdRng::seed_from_u64(seed);
+            let points: Vec<(f32, f32)> = (0..150)
+                .map(|_| (rng.random_range(0.0..1000.0), rng.random_range(0.0..1000.0)))
+                .collect();
+            let mut tour: Vec<usize> = (0..points.len()).collect();
+            tour.shuffle(&mut rng);
+            for closed in [true, false] {
+                let mut repaired: Vec<usize> = tour.clone();
+                assert!(uncross(&euclidean(&points, closed), &mut repaired));
+                assert_no_crossings(&points, &repaired, closed);
+            }
+        }
+    }
+
+    #[test]
+    fn star_polygon_becomes_simple() {
+        // Visiting points on a circle 37 steps apart crosses almost every other edge.
+        let n: usize = 101;
+        let points: Vec<(f32, f32)> = (0..n)
+            .map(|i| {
+                let angle: f32 = std::f32::consts::TAU * i as f32 / n as f32;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        let mut tour: Vec<usize> = (0..n).map(|i| (i * 37) % n).collect();
+        assert!(uncross(&euclidean(&points, true), &mut tour));
+        assert_no_crossings(&points, &tour, true);
+    }
+
+    #[test]
+    fn zigzag_between_two_lines_becomes_simple() {
+        // Every edge jumps between two parallel lines and crosses its neighbors' ones.
+        let points: Vec<(f32, f32)> = (0..80)
+            .map(|i| ((i / 2) as f32, if i % 2 == 0 { 0.0 } else { 10.0 }))
+            .collect();
+        let mut tour: Vec<usize> = (0..80).map(|i| (i * 2) % 80 + (i * 2) / 80).collect();
+        tour.reverse();
+        tour.swap(3, 40);
+        assert!(uncross(&euclidean(&points, true), &mut tour));
+        assert_no_crossings(&points, &tour, true);
+    }
+
+    #[test]
+    fn open_path_keeps_its_ends() {
+        let mut rng: StdRng = StdRng::seed_from_u64(7);
+        let points: Vec<(f32, f32)> = (0..200)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect();
+        let mut tour: Vec<usize> = (0..points.len()).collect();
+        tour.shuffle(&mut rng);
+        let (first, last) = (tour[0], tour[199]);
+        assert!(uncross(&euclidean(&points, false), &mut tour));
+        assert_no_crossings(&points, &tour, false);
+        assert_eq!((tour[0], tour[199]), (first, last));
+    }
+
+    #[test]
+    fn coincident_stipple_points_do_not_count_as_crossings() {
+        // Stipple points are rounded to pixels, so many share a position.
+        let mut rng: StdRng = StdRng::seed_from_u64(5);
+        let points: Vec<(f32, f32)> = (0..2000)
+            .map(|_| {
+                (
+                    rng.random_range(0..40) as f32,
+                    rng.random_range(0..40) as f32,
+                )
+            })
+            .collect();
+        let config = TourConfig::default();
+        let built: Vec<usize> = StrategyRegistry::with_defaults()
+            .build_tour("auto", &points, &config)
+            .unwrap()
+            .into_order();
+        let mut shuffled: Vec<usize> = built.clone();
+        shuffled.shuffle(&mut rng);
+        for mut tour in [built, shuffled] {
+            for closed in [true, false] {
+                assert!(uncross(&euclidean(&points, closed), &mut tour));
+                assert_no_crossings(&points, &tour, closed);
+            }
+        }
+    }
+
+    #[test]
+    fn crossings_stay_when_removing_them_costs_more() {
+        let mut rng: StdRng = StdRng::seed_from_u64(3);
+        let points: Vec<(f32, f32)> = (0..150)
+            .map(|_| (rng.random_range(0.0..100.0), rng.random_range(0.0..100.0)))
+            .collect();
+        let mut tour: Vec<usize> = (0..points.len()).collect();
+        tour.shuffle(&mut rng);
+        for metric in [Metric::Manhattan, Metric::Euclidean] {
+            let cost = EdgeCost::new(&points, metric, None, None, 50.0);
+            let before: f32 = cost.tour_length(&tour);
+            let mut repaired: Vec<usize> = tour.clone();
+            uncross(&cost, &mut repaired);
+            assert!(cost.tour_length(&repaired) <= before);
+        }
+    }
+}
@@ -1,69 +1,100 @@
-use rayon::prelude::*;
+use super::local_search::LocalSearch;
+use super::path::EdgeCost;
+use super::tour_strategy::{Tour, TourConfig, TourError};
+use std::time::{Duration, Instant};
 
-pub fn distance(p1: (f32, f32), p2: (f32, f32)) -> f32 {
-    let dx = p2.0 - p1.0;
-    let dy = p2.1 - p1.1;
-    dx * dx + dy * dy
+/// Runs neighbor-list 2-opt on the closed `tour` with the metric and brightness penalty
+/// of `config` until a local optimum or until the time budget of `config` is spent.
+pub fn two_opt(points: &[(f32, f32)], tour: &mut [usize], config: &TourConfig) {
+    LocalSearch {
+        metric: config.metric,
+        time_budget: config.time_budget,
+        brightness: config.brightness.clone(),
+        or_opt: false,
+        ..LocalSearch::default()
+    }
+    .optimize(points, tour);
 }
 
-pub fn two_opt(points: &[(f32, f32)], tour: &mut Vec<usize>) {
-    let n = tour.len();
-    let mut improved = true;
-    let max_iterations = 5;
-    let mut iteration = 0;
-
-    while improved && iteration < max_iterations {
-        improved = false;
-        iteration += 1;
-
-        let chunk_size = n.max(100) / rayon::current_num_threads();
-        let improvements: Vec<Option<(usize, usize, f32)>> = (0..n)
-            .step_by(chunk_size)
-            .collect::<Vec<_>>()
-            .par_iter()
-            .map(|&chunk_start| {
-                let chunk_end = (chunk_start + chunk_size).min(n - 1);
-                let mut best_improvement = None;
-
-                for i in chunk_start..chunk_end {
-                    if i >= n - 1 {
-                        break;
-                    }
+/// Runs interleaved 2-opt and Or-opt until a local optimum or until the time budget
+/// of `config` is spent. When `config` asks for an open path, the closed `tour` is cut
+/// into one first.
+pub fn two_opt_or_opt(points: &[(f32, f32)], tour: &mut [usize], config: &TourConfig) {
+    EdgeCost::from_config(points, config).cut(tour);
+    LocalSearch {
+        metric: config.metric,
+        time_budget: config.time_budget,
+        path: config.path,
+        brightness: config.brightness.clone(),
+        turn_penalty: config.turn_penalty,
+        ..LocalSearch::default()
+    }
+    .optimize(points, tour);
+}
 
-                    for j in i + 2..n {
-                        if i == 0 && j == n - 1 {
-                            continue;
-                        }
+/// Time left until `deadline`, zero once it passed, or None without a deadline.
+pub fn remaining(deadline: Option<Instant>) -> Option<Duration> {
+    deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
 
-                        let old_cost = distance(points[tour[i]], points[tour[i + 1]])
-                            + distance(points[tour[j]], points[tour[(j + 1) % n]]);
-                        let new_cost = distance(points[tour[i]], points[tour[j]])
-                            + distance(points[tour[i + 1]], points[tour[(j + 1) % n]]);
+/// Union-find root of `idx`, halving the path on the way.
+pub fn find(parent: &mut [usize], mut idx: usize) -> usize {
+    while parent[idx] != idx {
+        parent[idx] = parent[parent[idx]];
+        idx = parent[idx];
+    }
+    idx
+}
 
-                        let improvement = old_cost - new_cost;
-                        if improvement > 0.0 {
-                            match best_improvement {
-                                None => best_improvement = Some((i, j, improvement)),
-                                Some((_, _, best_imp)) if improvement > best_imp => {
-                                    best_improvement = Some((i, j, improvement));
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
+/// Builds the tour with `build` over one point per distinct position and visits the
+/// other points right after the point at their position, over zero-length edges. The
+/// kd-trees hold at most 32 points at exactly the same position, so every strategy
+/// that uses one runs through this.
+pub fn on_distinct_positions(
+    points: &[(f32, f32)],
+    build: impl FnOnce(&[(f32, f32)]) -> Result<Tour, TourError>,
+) -> Result<Tour, TourError> {
+    let indices: Vec<usize> = (0..points.len()).collect();
+    let groups: Vec<Vec<usize>> = group_by_position(points, &indices);
+    if groups.len() == points.len() {
+        return build(points);
+    }
+    let distinct: Vec<(f32, f32)> = groups.iter().map(|group| points[group[0]]).collect();
+    let tour: Tour = build(&distinct)?;
+    Ok(Tour::new(
+        tour.order()
+            .iter()
+            .flat_map(|&group| groups[group].iter().copied())
+            .collect(),
+    ))
+}
 
-                best_improvement
-            })
-            .collect();
+/// Groups `indices` by the position of their points, with -0.0 and 0.0 treated as
+/// equal. Groups are in the order of their first index in `indices`, and keep the
+/// order of `indices` within.
+pub fn group_by_position(points: &[(f32, f32)], indices: &[usize]) -> Vec<Vec<usize>> {
+    let position = |i: usize| {
+        let (x, y) = points[indices[i]];
+        (x + 0.0, y + 0.0)
+    };
+    let mut sorted: Vec<usize> = (0..indices.len()).collect();
+    sorted.sort_by(|&a, &b| {
+        let (p, q) = (position(a), position(b));
+        p.0.total_cmp(&q.0)
+            .then(p.1.total_cmp(&q.1))
+            .then(a.cmp(&b))
+    });
 
-        if let Some((i, j, _)) = improvements
-            .into_iter()
-            .flatten()
-            .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
-        {
-            tour[i + 1..=j].reverse();
-            improved = true;
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for i in sorted {
+        match groups.last_mut() {
+            Some(group) if position(group[0]) == position(i) => group.push(i),
+            _ => groups.push(vec![i]),
         }
     }
+    groups.sort_by_key(|group| group[0]);
+    groups
+        .into_iter()
+        .map(|group| group.into_iter().map(|i| indices[i]).collect())
+        .collect()
 }
@@ -0,0 +1,78 @@
+use super::tour_strategy::{Tour, TourConfig, TourError};
+use super::utils::{group_by_position, two_opt_or_opt};
+use kiddo::{KdTree, SquaredEuclidean};
+
+/// Tour of `points` that follows `previous_tour` over `previous_points`, e.g. the
+/// tour of the previous frame of a video, so that the line keeps its shape from one
+/// frame to the next instead of being rebuilt from scratch.
+///
+/// Every point is projected onto the previous tour next to its nearest previous point,
+/// and the points are ordered by where along the tour their projections lie. The order
+/// is then repaired with 2-opt and Or-opt, which only changes it locally.
+pub fn warm_start_tour(
+    previous_points: &[(f32, f32)],
+    previous_tour: &[usize],
+    points: &[(f32, f32)],
+    config: &TourConfig,
+) -> Result<Tour, TourError> {
+    if points.is_empty() || previous_tour.is_empty() {
+        return Err(TourError::EmptyInput);
+    }
+    let len: usize = previous_tour.len();
+    let closed: bool = config.path.is_none();
+    // Only the first visit of every previous position goes into the kd-tree, which
+    // holds at most 32 points at exactly the same position.
+    let mut kdtree = KdTree::<f32, 2>::with_capacity(len);
+    let along: Vec<(f32, f32)> = previous_tour
+        .iter()
+        .map(|&idx| previous_points[idx])
+        .collect();
+    let positions: Vec<usize> = (0..len).collect();
+    for group in group_by_position(&along, &positions) {
+        let (x, y) = along[group[0]];
+        kdtree.add(&[x, y], group[0] as u64);
+    }
+
+    // Position along the previous tour of the projection onto one of the two edges
+    // at the nearest previous point, counted in edges from its start.
+    let keys: Vec<f32> = points
+        .iter()
+        .map(|&p| {
+            let position: usize = kdtree.nearest_one::<SquaredEuclidean>(&[p.0, p.1]).item as usize;
+            let at = |position: usize| previous_points[previous_tour[position % len]];
+            let mut best: (f32, f32) = (f32::INFINITY, position as f32);
+            // The ends of an open path only have an edge on one side.
+            if closed || position + 1 < len {
+                let (distance, t) = project(p, at(position), at(position + 1));
+                best = (distance, position as f32 + t);
+            }
+            if closed || position > 0 {
+                let before: usize = (position + len - 1) % len;
+                let (distance, t) = project(p, at(before), at(position));
+                if distance < best.0 {
+                    best = (distance, before as f32 + t);
+                }
+            }
+            best.1
+        })
+        .collect();
+
+    let mut tour: Vec<usize> = (0..points.len()).collect();
+    tour.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+    two_opt_or_opt(points, &mut tour, config);
+    Ok(Tour::new(tour))
+}
+
+/// Squared distance from `p` to the segment (a, b), and the parameter in 0..=1 of the
+/// closest point on it.
+fn project(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq: f32 = dx * dx + dy * dy;
+    let t: f32 = if length_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, y) = (a.0 + t * dx - p.0, a.1 + t * dy - p.1);
+    (x * x + y * y, t)
+}
@@ -0,0 +1,247 @@
+use crate::tour_generation::stats::validate_tour;
+use crate::tour_generation::tour_strategy::{Tour, TourError};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum TsplibError {
+    Io(io::Error),
+    /// A line of the file could not be understood.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// The file has no TOUR_SECTION.
+    MissingTourSection,
+    /// The tour is for a different number of points.
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The tour does not visit every point exactly once.
+    InvalidTour(TourError),
+}
+
+impl fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsplibError::Io(err) => write!(f, "{}", err),
+            TsplibError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            TsplibError::MissingTourSection => write!(f, "no TOUR_SECTION found"),
+            TsplibError::DimensionMismatch { expected, found } => {
+                write!(
+                    f,
+                    "tour has {} nodes but there are {} points",
+                    found, expected
+                )
+            }
+            TsplibError::InvalidTour(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for TsplibError {}
+
+impl From<io::Error> for TsplibError {
+    fn from(err: io::Error) -> Self {
+        TsplibError::Io(err)
+    }
+}
+
+/// Writes `points` as a symmetric EUC_2D problem. Coordinates are multiplied by
+/// `scale` and rounded, since solvers round EUC_2D distances to integers.
+pub fn write_tsp(path: &Path, name: &str, points: &[(f32, f32)], scale: f32) -> io::Result<()> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    writeln!(file, "NAME : {}", name)?;
+    writeln!(file, "COMMENT : coordinates scaled by {}", scale)?;
+    writeln!(file, "TYPE : TSP")?;
+    writeln!(file, "DIMENSION : {}", points.len())?;
+    writeln!(file, "EDGE_WEIGHT_TYPE : EUC_2D")?;
+    writeln!(file, "NODE_COORD_SECTION")?;
+    for (idx, &(x, y)) in points.iter().enumerate() {
+        writeln!(
+            file,
+            "{} {} {}",
+            idx + 1,
+            (x * scale).round() as i64,
+            (y * scale).round() as i64
+        )?;
+    }
+    writeln!(file, "EOF")?;
+    file.flush()
+}
+
+/// Writes `tour` with the 1-based node ids TSPLIB uses.
+pub fn write_tour(path: &Path, name: &str, tour: &[usize]) -> io::Result<()> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    writeln!(file, "NAME : {}", name)?;
+    writeln!(file, "TYPE : TOUR")?;
+    writeln!(file, "DIMENSION : {}", tour.len())?;
+    writeln!(file, "TOUR_SECTION")?;
+    for &idx in tour {
+        writeln!(file, "{}", idx + 1)?;
+    }
+    writeln!(file, "-1")?;
+    writeln!(file, "EOF")?;
+    file.flush()
+}
+
+/// Reads a TSPLIB tour, e.g. written by LKH or Concorde for a file from
+/// [`write_tsp`], and checks that it visits each of the `num_points` points once.
+pub fn read_tour(path: &Path, num_points: usize) -> Result<Tour, TsplibError> {
+    parse_tour(&fs::read_to_string(path)?, num_points)
+}
+
+/// Parses the contents of a TSPLIB tour file, see [`read_tour`].
+pub fn parse_tour(contents: &str, num_points: usize) -> Result<Tour, TsplibError> {
+    let mut dimension: Option<usize> = None;
+    let mut order: Option<Vec<usize>> = None;
+
+    for (number, line) in contents.lines().enumerate() {
+        let line: &str = line.trim();
+        let parse_error = |message: String| TsplibError::Parse {
+            line: number + 1,
+            message,
+        };
+
+        if let Some(order) = order.as_mut() {
+            for token in line.split_whitespace() {
+                if token == "-1" || token == "EOF" {
+                    return finish(std::mem::take(order), dimension, num_points);
+                }
+                let id: usize = token
+                    .parse()
+                    .map_err(|_| parse_error(format!("invalid node id '{}'", token)))?;
+                if id == 0 {
+                    return Err(parse_error("node ids start at 1".to_string()));
+                }
+                order.push(id - 1);
+            }
+        } else if line.starts_with("TOUR_SECTION") {
+            order = Some(Vec::with_capacity(dimension.unwrap_or(num_points)));
+        } else if let Some((key, value)) = line.split_once(':')
+            && key.trim() == "DIMENSION"
+        {
+            let value: &str = value.trim();
+            dimension = Some(
+                value
+                    .parse()
+                    .map_err(|_| parse_error(format!("invalid dimension '{}'", value)))?,
+            );
+        } else if line == "EOF" {
+            break;
+        }
+    }
+
+    match order {
+        Some(order) => finish(order, dimension, num_points),
+        None => Err(TsplibError::MissingTourSection),
+    }
+}
+
+fn finish(
+    order: Vec<usize>,
+    dimension: Option<usize>,
+    num_points: usize,
+) -> Result<Tour, TsplibError> {
+    let found: usize = dimension.unwrap_or(order.len());
+    if found != num_points {
+        return Err(TsplibError::DimensionMismatch {
+            expected: num_points,
+            found,
+        });
+    }
+    validate_tour(num_points, &order).map_err(TsplibError::InvalidTour)?;
+    Ok(Tour::new(order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tsp_art_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn written_tour_reads_back() {
+        let path = temp_path("round_trip.tour");
+        let order: Vec<usize> = vec![3, 0, 4, 1, 2];
+        write_tour(&path, "round trip", &order).unwrap();
+        let tour: Tour = read_tour(&path, 5).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(tour.order(), order.as_slice());
+    }
+
+    #[test]
+    fn written_problem_has_scaled_coordinates() {
+        let path = temp_path("problem.tsp");
+        write_tsp(&path, "problem", &[(1.234, 5.0), (0.0, 2.5)], 100.0).unwrap();
+        let contents: String = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert!(lines.contains(&"DIMENSION : 2"));
+        assert!(lines.contains(&"EDGE_WEIGHT_TYPE : EUC_2D"));
+        let section: usize = lines
+            .iter()
+            .position(|&l| l == "NODE_COORD_SECTION")
+            .unwrap();
+        assert_eq!(&lines[section + 1..], &["1 123 500", "2 0 250", "EOF"]);
+    }
+
+    #[test]
+    fn parses_solver_output() {
+        let contents = "NAME : out\nCOMMENT : Length = 42\nTYPE : TOUR\nDIMENSION : 4\n\
+                        TOUR_SECTION\n2 4\n  3\n1\n-1\nEOF\n";
+        assert_eq!(parse_tour(contents, 4).unwrap().order(), &[1, 3, 2, 0]);
+        // The terminator is optional and the dimension can be left out.
+        assert_eq!(
+            parse_tour("TOUR_SECTION\n1\n3\n2\n", 3).unwrap().order(),
+            &[0, 2, 1]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_tours() {
+        assert!(matches!(
+            parse_tour("DIMENSION : 3\n1\n2\n3\n", 3),
+            Err(TsplibError::MissingTourSection)
+        ));
+        assert!(matches!(
+            parse_tour("TOUR_SECTION\n1\nx\n2\n-1\n", 3),
+            Err(TsplibError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            parse_tour("TOUR_SECTION\n0\n1\n2\n-1\n", 3),
+            Err(TsplibError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_tour("DIMENSION : three\nTOUR_SECTION\n1\n-1\n", 3),
+            Err(TsplibError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse_tour("DIMENSION : 4\nTOUR_SECTION\n1\n2\n3\n4\n-1\n", 3),
+            Err(TsplibError::DimensionMismatch {
+                expected: 3,
+                found: 4
+            })
+        ));
+        assert!(matches!(
+            parse_tour("TOUR_SECTION\n1\n2\n2\n-1\n", 3),
+            Err(TsplibError::InvalidTour(TourError::DuplicateIndex(1)))
+        ));
+        assert!(matches!(
+            parse_tour("TOUR_SECTION\n1\n2\n5\n-1\n", 3),
+            Err(TsplibError::InvalidTour(TourError::IndexOutOfRange {
+                index: 4,
+                len: 3
+            }))
+        ));
+        assert!(matches!(
+            parse_tour("DIMENSION : 3\nTOUR_SECTION\n1\n3\n-1\n", 3),
+            Err(TsplibError::InvalidTour(TourError::MissingIndex(1)))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+use tsp_art::tour_generation::hull::convex_hull;
+use tsp_art::tour_generation::optimizer::{TourOptimizer, optimize_chain};
+use tsp_art::tour_generation::path::PathEnds;
+use tsp_art::tour_generation::registry::StrategyRegistry;
+use tsp_art::tour_generation::stats::validate_tour;
+use tsp_art::tour_generation::tour_strategy::{TourConfig, TourError};
+use tsp_art::tour_generation::warm_start::warm_start_tour;
+
+fn config() -> TourConfig {
+    TourConfig {
+        time_budget: Some(Duration::from_millis(20)),
+        ..TourConfig::default()
+    }
+}
+
+/// Builds a tour with every registered strategy, which the registry validates.
+fn assert_every_strategy_succeeds(points: &[(f32, f32)], config: &TourConfig) {
+    let registry = StrategyRegistry::with_defaults();
+    for name in registry.names() {
+        let tour = registry
+            .build_tour(name, points, config)
+            .unwrap_or_else(|err| panic!("{} failed on {} points: {}", name, points.len(), err));
+        assert_eq!(tour.len(), points.len(), "{}", name);
+    }
+}
+
+/// Points on a line, in a scrambled order.
+fn collinear(n: usize) -> Vec<(f32, f32)> {
+    (0..n)
+        .map(|i| {
+            let t: f32 = ((i * 7) % n) as f32;
+            (2.0 * t + 1.0, 3.0 * t)
+        })
+        .collect()
+}
+
+/// A few positions, each repeated many times.
+fn duplicates(n: usize) -> Vec<(f32, f32)> {
+    let positions: [(f32, f32); 4] = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (5.0, 3.0)];
+    (0..n).map(|i| positions[i % positions.len()]).collect()
+}
+
+#[test]
+fn hull_of_square_skips_interior_and_edge_points() {
+    let points = [
+        (0.0, 0.0),
+        (1.0, 0.0),
+        (2.0, 0.0),
+        (1.0, 1.0),
+        (2.0, 2.0),
+        (0.0, 2.0),
+    ];
+    assert_eq!(convex_hull(&points), vec![0, 2, 4, 5]);
+}
+
+#[test]
+fn hull_of_collinear_points_is_their_ends() {
+    let points: Vec<(f32, f32)> = collinear(50);
+    let mut hull: Vec<usize> = convex_hull(&points);
+    hull.sort();
+    let first: usize = (0..50).position(|i| (i * 7) % 50 == 0).unwrap();
+    let last: usize = (0..50).position(|i| (i * 7) % 50 == 49).unwrap();
+    let mut expected: Vec<usize> = vec![first, last];
+    expected.sort();
+    assert_eq!(hull, expected);
+}
+
+#[test]
+fn hull_uses_each_position_once() {
+    let points: Vec<(f32, f32)> = duplicates(40);
+    assert_eq!(convex_hull(&points), vec![0, 1, 2]);
+
+    let same: Vec<(f32, f32)> = vec![(3.0, 4.0); 10];
+    assert_eq!(convex_hull(&same), vec![0]);
+    assert_eq!(convex_hull(&[(0.0, 0.0), (-0.0, 0.0)]), vec![0]);
+}
+
+#[test]
+fn hull_of_fewer_than_three_points() {
+    assert_eq!(convex_hull(&[]), Vec::<usize>::new());
+    assert_eq!(convex_hull(&[(1.0, 1.0)]), vec![0]);
+    assert_eq!(convex_hull(&[(1.0, 1.0), (0.0, 0.0)]), vec![1, 0]);
+}
+
+#[test]
+fn strategies_reject_empty_input() {
+    let registry = StrategyRegistry::with_defaults();
+    for name in registry.names() {
+        assert_eq!(
+            registry.build_tour(name, &[], &config()).unwrap_err(),
+            TourError::EmptyInput,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn strategies_handle_fewer_than_three_points() {
+    assert_every_strategy_succeeds(&[(4.0, 2.0)], &config());
+    assert_every_strategy_succeeds(&[(4.0, 2.0), (1.0, 7.0)], &config());
+    assert_every_strategy_succeeds(&[(4.0, 2.0), (4.0, 2.0)], &config());
+}
+
+#[test]
+fn strategies_handle_collinear_points() {
+    assert_every_strategy_succeeds(&collinear(3), &config());
+    assert_every_strategy_succeeds(&collinear(200), &config());
+}
+
+#[test]
+fn strategies_handle_duplicate_points() {
+    assert_every_strategy_succeeds(&duplicates(120), &config());
+    assert_every_strategy_succeeds(&[(1.0, 1.0); 30], &config());
+}
+
+#[test]
+fn strategies_handle_more_coincident_points_than_a_kd_tree_leaf() {
+    let mut points: Vec<(f32, f32)> = vec![(5.0, 5.0); 40];
+    points.extend((0..100).map(|i| ((i % 10) as f32, (i / 10) as f32)));
+    assert_every_strategy_succeeds(&points, &config());
+    assert_every_strategy_succeeds(&[(1.0, 1.0); 100], &config());
+
+    let path = TourConfig {
+        path: Some(PathEnds {
+            start: Some((0.0, 0.0)),
+            end: Some((9.0, 9.0)),
+        }),
+        ..config()
+    };
+    assert_every_strategy_succeeds(&points, &path);
+}
+
+#[test]
+fn optimizers_handle_more_coincident_points_than_a_kd_tree_leaf() {
+    let mut points: Vec<(f32, f32)> = vec![(5.0, 5.0); 40];
+    points.extend((0..100).map(|i| ((i % 10) as f32, (i / 10) as f32)));
+    let mut tour: Vec<usize> = (0..points.len()).collect();
+    optimize_chain(
+        &points,
+        &mut tour,
+        &config(),
+        &[
+            TourOptimizer::TwoOpt,
+            TourOptimizer::OrOpt,
+            TourOptimizer::LinKernighan,
+        ],
+    );
+    validate_tour(points.len(), &tour).unwrap();
+
+    let warm: Vec<usize> = warm_start_tour(&points, &tour, &points, &config())
+        .unwrap()
+        .into_order();
+    validate_tour(points.len(), &warm).unwrap();
+}
+
+#[test]
+fn strategies_handle_degenerate_open_paths() {
+    let path = TourConfig {
+        path: Some(PathEnds {
+            start: Some((0.0, 0.0)),
+            end: None,
+        }),
+        ..config()
+    };
+    assert_every_strategy_succeeds(&collinear(100), &path);
+    assert_every_strategy_succeeds(&duplicates(100), &path);
+}
+
+#[test]
+fn strategies_start_small_paths_at_the_pinned_end() {
+    let points = [(0.0, 0.0), (5.0, 5.0), (9.0, 1.0)];
+    let path = TourConfig {
+        path: Some(PathEnds {
+            start: Some((9.0, 0.0)),
+            end: None,
+        }),
+        ..config()
+    };
+    let registry = StrategyRegistry::with_defaults();
+    for name in registry.names() {
+        let tour = registry.build_tour(name, &points, &path).unwrap();
+        assert_eq!(tour.order()[0], 2, "{}", name);
+    }
+}
+
+#[test]
+fn hilbert_path_ends_at_the_pinned_ends() {
+    let points: Vec<(f32, f32)> = (0..100)
+        .map(|i| ((i % 10) as f32, (i / 10) as f32))
+        .collect();
+    let path = TourConfig {
+        path: Some(PathEnds {
+            start: Some((9.0, 9.0)),
+            end: Some((9.0, 0.0)),
+        }),
+        ..config()
+    };
+    let tour = StrategyRegistry::with_defaults()
+        .build_tour("hilbert", &points, &path)
+        .unwrap();
+    assert_eq!((tour.order()[0], tour.order()[99]), (99, 9));
+}
+
+#[test]
+fn strategies_handle_a_spent_time_budget() {
+    // Greedy finishes the construction without its full scans once the budget is spent.
+    let points: Vec<(f32, f32)> = (0..3000)
+        .map(|i| {
+            (
+                ((i * 37) % 101) as f32 + 0.5 * (i % 7) as f32,
+                ((i * 53) % 97) as f32,
+            )
+        })
+        .collect();
+    let config = TourConfig {
+        time_budget: Some(Duration::ZERO),
+        ..TourConfig::default()
+    };
+    assert_every_strategy_succeeds(&points, &config);
+}